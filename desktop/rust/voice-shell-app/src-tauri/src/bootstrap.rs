@@ -0,0 +1,239 @@
+// Python Environment Bootstrap
+// Private venv with streamware installed offline from bundled wheels
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use crate::python::{self, Resolver, Source};
+use crate::settings::APP_DIR;
+
+/// Extra directory of wheels to install from, e.g. for development builds
+pub const WHEEL_DIR_ENV: &str = "STREAMWARE_WHEEL_DIR";
+
+/// Distribution name of the backend package
+const PACKAGE: &str = "streamware";
+
+/// Bootstrap stage, in order
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    FindPython,
+    CreateVenv,
+    Install,
+    Verify,
+    Done,
+}
+
+/// One progress update, sent as the `bootstrap-progress` event
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Progress {
+    pub step: Step,
+    pub message: String,
+}
+
+/// Location of the private environment in the platform data directory
+pub fn venv_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(APP_DIR)
+        .join("venv")
+}
+
+/// Creates or repairs the private environment
+#[derive(Debug, Clone)]
+pub struct Bootstrap {
+    /// Environment to (re)create
+    pub venv: PathBuf,
+    /// Directories searched for wheels; pip never touches the network
+    pub wheel_dirs: Vec<PathBuf>,
+}
+
+impl Bootstrap {
+    /// Bootstrap into `venv_dir()` from `bundled` and `WHEEL_DIR_ENV`
+    pub fn new(bundled: Option<PathBuf>) -> Self {
+        let wheel_dirs = std::env::var_os(WHEEL_DIR_ENV)
+            .map(PathBuf::from)
+            .into_iter()
+            .chain(bundled)
+            .collect();
+        Self { venv: venv_dir(), wheel_dirs }
+    }
+
+    /// Create the venv from scratch, install the backend and verify it.
+    ///
+    /// Returns the interpreter of the new environment.
+    pub async fn run(&self, mut on_progress: impl FnMut(Progress)) -> Result<PathBuf, String> {
+        let mut report = |step, message: String| {
+            log::info!("[bootstrap] {}", message);
+            on_progress(Progress { step, message });
+        };
+
+        let wheel_dirs: Vec<&PathBuf> = self.wheel_dirs.iter().filter(|dir| has_package_wheel(dir)).collect();
+        if wheel_dirs.is_empty() {
+            return Err(format!(
+                "No {} wheel found in {:?}; set {} to a directory of wheels",
+                PACKAGE, self.wheel_dirs, WHEEL_DIR_ENV
+            ));
+        }
+
+        report(Step::FindPython, "Looking for a Python interpreter".to_string());
+        let base = find_base_python(self.venv.clone()).await?;
+        report(Step::FindPython, format!("Using {}", base.display()));
+
+        report(Step::CreateVenv, format!("Creating environment in {}", self.venv.display()));
+        let mut venv = Command::new(&base);
+        venv.arg("-m").arg("venv").arg("--clear").arg(&self.venv);
+        run_streaming(venv, Step::CreateVenv, &mut report).await?;
+
+        let python = python::env_interpreters(&self.venv)
+            .find(|path| path.is_file())
+            .ok_or_else(|| format!("{} has no Python interpreter", self.venv.display()))?;
+
+        report(Step::Install, format!("Installing {} from {} wheel directories", PACKAGE, wheel_dirs.len()));
+        let mut install = Command::new(&python);
+        install.args(["-m", "pip", "install", "--no-index", "--disable-pip-version-check", "--upgrade"]);
+        for dir in &wheel_dirs {
+            install.arg("--find-links").arg(dir);
+        }
+        install.arg(PACKAGE);
+        run_streaming(install, Step::Install, &mut report).await?;
+
+        report(Step::Verify, "Checking the backend imports".to_string());
+        let checked = python.clone();
+        let candidate = tokio::task::spawn_blocking(move || python::check(Source::Managed, &checked))
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some(reason) = candidate.rejected {
+            return Err(format!("Installed environment is not usable: {}", reason));
+        }
+
+        report(Step::Done, format!("Python environment ready: {}", python.display()));
+        Ok(python)
+    }
+}
+
+/// Any supported interpreter, with or without streamware, outside `venv`
+async fn find_base_python(venv: PathBuf) -> Result<PathBuf, String> {
+    tokio::task::spawn_blocking(move || {
+        let resolver = Resolver { managed: None, ..Resolver::from_env(None) };
+        resolver
+            .candidates()
+            .into_iter()
+            .filter(|(_, path)| !path.starts_with(&venv))
+            .map(|(source, path)| python::check(source, &path))
+            .find(|candidate| {
+                let version = candidate.version.as_deref().and_then(python::parse_version);
+                version.is_some_and(python::is_supported)
+            })
+            .map(|candidate| candidate.path)
+            .ok_or_else(|| {
+                format!(
+                    "Python {}.{} or newer is required to set up the backend",
+                    python::MIN_VERSION.0,
+                    python::MIN_VERSION.1
+                )
+            })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Whether `dir` holds a wheel of the backend package
+fn has_package_wheel(dir: &Path) -> bool {
+    let prefix = format!("{}-", PACKAGE);
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries.filter_map(Result::ok).any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                name.starts_with(&prefix) && name.ends_with(".whl")
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Run `command`, reporting each output line, and fail on a non-zero exit
+async fn run_streaming(
+    mut command: Command,
+    step: Step,
+    report: &mut impl FnMut(Step, String),
+) -> Result<(), String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {:?}: {}", command.as_std().get_program(), e))?;
+
+    let (lines, mut received) = mpsc::unbounded_channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, lines.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, lines);
+    }
+
+    // Keep the last line to explain a failure
+    let mut last = String::new();
+    while let Some(line) = received.recv().await {
+        if !line.trim().is_empty() {
+            report(step, line.clone());
+            last = line;
+        }
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{:?} failed ({}): {}", step, status, last))
+    }
+}
+
+fn forward_lines(stream: impl AsyncRead + Unpin + Send + 'static, lines: mpsc::UnboundedSender<String>) {
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = reader.next_line().await {
+            if lines.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_streaming_reports_lines_and_failure() {
+        let mut lines = Vec::new();
+        let mut report = |_, line| lines.push(line);
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo Collecting streamware; echo 'no matching distribution' >&2; exit 1"]);
+        let result = run_streaming(command, Step::Install, &mut report).await;
+
+        lines.sort();
+        assert_eq!(lines, ["Collecting streamware", "no matching distribution"]);
+        assert!(result.unwrap_err().contains("no matching distribution"));
+    }
+
+    #[tokio::test]
+    async fn test_requires_a_wheel() {
+        let empty = std::env::temp_dir().join(format!("voice-shell-wheels-{}", std::process::id()));
+        std::fs::create_dir_all(&empty).unwrap();
+        std::fs::write(empty.join("other-1.0-py3-none-any.whl"), "").unwrap();
+        assert!(!has_package_wheel(&empty));
+
+        let bootstrap = Bootstrap { venv: empty.join("venv"), wheel_dirs: vec![empty.clone()] };
+        let error = bootstrap.run(|_| {}).await.unwrap_err();
+        assert!(error.contains("No streamware wheel"));
+
+        std::fs::write(empty.join("streamware-0.2.0-py3-none-any.whl"), "").unwrap();
+        assert!(has_package_wheel(&empty));
+    }
+}
@@ -0,0 +1,282 @@
+// Backend Output Classifier
+// Severity and structure of Python stdout/stderr lines
+
+use crate::logs::Level;
+
+/// First line of a Python traceback
+const TRACEBACK_HEADER: &str = "Traceback (most recent call last):";
+
+/// Lines joining chained tracebacks
+const CHAIN_HEADERS: [&str; 2] = [
+    "During handling of the above exception, another exception occurred:",
+    "The above exception was the direct cause of the following exception:",
+];
+
+/// Status icons printed by `VoiceShellServer` that are not informational
+const ICON_LEVELS: [(char, Level); 5] = [
+    ('❌', Level::Error),
+    ('⛔', Level::Error),
+    ('🚫', Level::Error),
+    ('⚠', Level::Warning),
+    ('❗', Level::Warning),
+];
+
+/// What a record was recognised as
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Kind {
+    /// Anything else
+    Text,
+    /// A Python `logging` record, e.g. `WARNING:root:...`
+    Logging { logger: Option<String> },
+    /// An emoji-prefixed status line, e.g. `📂 Restored 3 sessions`
+    Status { icon: String },
+    /// A whole traceback; `exception` is its last line
+    Traceback { exception: String },
+    /// A `warnings.warn` message, e.g. `DeprecationWarning`
+    PythonWarning { category: String },
+}
+
+/// One classified line, or several for a traceback
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Record {
+    pub level: Level,
+    #[serde(flatten)]
+    pub kind: Kind,
+    pub message: String,
+}
+
+impl Record {
+    /// Unclassified text at `level`
+    pub fn text(level: Level, message: &str) -> Self {
+        Self { level, kind: Kind::Text, message: message.to_string() }
+    }
+}
+
+/// Classifies the lines of one output stream, grouping tracebacks
+pub struct Classifier {
+    /// Level of lines that are not recognised
+    default: Level,
+    /// Lines of the traceback being collected
+    traceback: Option<Vec<String>>,
+}
+
+impl Classifier {
+    /// Classifier falling back to `default`, e.g. warning for stderr
+    pub fn new(default: Level) -> Self {
+        Self { default, traceback: None }
+    }
+
+    /// Classify the next line; returns the record it completes, if any
+    pub fn feed(&mut self, line: &str) -> Option<Record> {
+        if let Some(ref mut lines) = self.traceback {
+            lines.push(line.to_string());
+            // Frames are indented; the first unindented line names the exception
+            let continues = line.is_empty()
+                || line.starts_with(char::is_whitespace)
+                || line.starts_with(TRACEBACK_HEADER)
+                || CHAIN_HEADERS.contains(&line);
+            return if continues { None } else { self.finish() };
+        }
+
+        if line.trim().is_empty() {
+            return None;
+        }
+        if line.starts_with(TRACEBACK_HEADER) || CHAIN_HEADERS.contains(&line) {
+            self.traceback = Some(vec![line.to_string()]);
+            return None;
+        }
+        Some(classify_line(line, self.default))
+    }
+
+    /// Flush a traceback cut short, e.g. when the stream closes
+    pub fn finish(&mut self) -> Option<Record> {
+        let lines = self.traceback.take()?;
+        let exception = lines
+            .iter()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+            .unwrap_or_default();
+        Some(Record {
+            level: Level::Error,
+            kind: Kind::Traceback { exception },
+            message: lines.join("\n").trim_end().to_string(),
+        })
+    }
+}
+
+/// Classify a single line that is not part of a traceback
+pub fn classify_line(line: &str, default: Level) -> Record {
+    if let Some(record) = parse_logging(line) {
+        return record;
+    }
+    if let Some(category) = parse_python_warning(line) {
+        return Record { level: Level::Warning, kind: Kind::PythonWarning { category }, message: line.to_string() };
+    }
+    if let Some((icon, level)) = parse_icon(line) {
+        return Record { level, kind: Kind::Status { icon }, message: line.trim().to_string() };
+    }
+    Record::text(default, line)
+}
+
+/// Level for a Python `logging` level name
+fn python_level(name: &str) -> Option<Level> {
+    match name {
+        "DEBUG" | "NOTSET" => Some(Level::Debug),
+        "INFO" => Some(Level::Info),
+        "WARNING" | "WARN" => Some(Level::Warning),
+        "ERROR" | "CRITICAL" | "FATAL" => Some(Level::Error),
+        _ => None,
+    }
+}
+
+/// Recognise the `logging` formats used by the backend and its libraries:
+/// `%(asctime)s - %(name)s - %(levelname)s - %(message)s` from
+/// `enable_diagnostics`, the `LEVEL:name:message` default, uvicorn's
+/// `LEVEL:     message` and Rich's `[time] LEVEL    message`
+fn parse_logging(line: &str) -> Option<Record> {
+    let parts: Vec<&str> = line.splitn(4, " - ").collect();
+    if let [_, logger, level, message] = parts[..] {
+        if let Some(level) = python_level(level.trim()) {
+            return Some(Record {
+                level,
+                kind: Kind::Logging { logger: Some(logger.trim().to_string()) },
+                message: message.to_string(),
+            });
+        }
+    }
+
+    // Rich prints the time in brackets, or blanks when it repeats
+    let mut rest = line.trim_start();
+    if rest.starts_with('[') {
+        rest = rest[rest.find(']')? + 1..].trim_start();
+    }
+    let end = rest.find(|c: char| c == ':' || c.is_whitespace())?;
+    let level = python_level(&rest[..end])?;
+    let rest = &rest[end..];
+
+    let (logger, message) = match rest.strip_prefix(':') {
+        Some(named) if !named.starts_with(char::is_whitespace) && named.contains(':') => {
+            let (logger, message) = named.split_once(':')?;
+            (Some(logger.to_string()), message)
+        }
+        Some(message) => (None, message),
+        None => (None, rest),
+    };
+    Some(Record { level, kind: Kind::Logging { logger }, message: message.trim().to_string() })
+}
+
+/// Category of a `path:line: CategoryWarning: message` line
+fn parse_python_warning(line: &str) -> Option<String> {
+    let index = line.find("Warning: ")?;
+    let head = &line[..index];
+    let start = head.rfind(": ")? + 2;
+    let category = &head[start..];
+    let named = !category.is_empty() && category.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    named.then(|| format!("{}Warning", category))
+}
+
+/// Leading emoji of a status line and its level
+fn parse_icon(line: &str) -> Option<(String, Level)> {
+    let mut chars = line.trim_start().chars();
+    let icon = chars.next()?;
+    if !is_emoji(icon) {
+        return None;
+    }
+    let level = ICON_LEVELS
+        .iter()
+        .find(|(c, _)| *c == icon)
+        .map_or(Level::Info, |(_, level)| *level);
+    Some((icon.to_string(), level))
+}
+
+/// Symbol and pictograph blocks the server's status icons come from
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x2190..=0x2BFF | 0x1F000..=0x1FAFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_all(classifier: &mut Classifier, lines: &[&str]) -> Vec<Record> {
+        let mut records: Vec<Record> = lines.iter().filter_map(|line| classifier.feed(line)).collect();
+        records.extend(classifier.finish());
+        records
+    }
+
+    #[test]
+    fn test_python_logging_formats() {
+        let record = classify_line("2024-05-01 10:00:00,123 - streamware.llm - ERROR - model missing", Level::Info);
+        assert_eq!(record.level, Level::Error);
+        assert_eq!(record.kind, Kind::Logging { logger: Some("streamware.llm".to_string()) });
+        assert_eq!(record.message, "model missing");
+
+        let record = classify_line("WARNING:root:disk almost full", Level::Info);
+        assert_eq!(record.level, Level::Warning);
+        assert_eq!(record.kind, Kind::Logging { logger: Some("root".to_string()) });
+        assert_eq!(record.message, "disk almost full");
+
+        let record = classify_line("INFO:     Started server process", Level::Warning);
+        assert_eq!((record.level, record.message.as_str()), (Level::Info, "Started server process"));
+
+        let record = classify_line("[10:00:01] DEBUG    frame 12 processed", Level::Warning);
+        assert_eq!((record.level, record.message.as_str()), (Level::Debug, "frame 12 processed"));
+    }
+
+    #[test]
+    fn test_status_icons_and_warnings() {
+        let record = classify_line("⚠️ Could not restore sessions: locked", Level::Info);
+        assert_eq!(record.level, Level::Warning);
+        assert_eq!(record.kind, Kind::Status { icon: "⚠".to_string() });
+
+        assert_eq!(classify_line("❌ websockets package required", Level::Info).level, Level::Error);
+        assert_eq!(classify_line("   ✅ Removed from memory", Level::Warning).level, Level::Info);
+        assert_eq!(classify_line("📂 Restored 3 sessions from database", Level::Warning).level, Level::Info);
+
+        let record = classify_line("/app/llm.py:12: DeprecationWarning: use generate()", Level::Info);
+        assert_eq!(record.kind, Kind::PythonWarning { category: "DeprecationWarning".to_string() });
+
+        assert_eq!(classify_line("Broadcast error: closed", Level::Warning), Record::text(Level::Warning, "Broadcast error: closed"));
+    }
+
+    #[test]
+    fn test_groups_tracebacks() {
+        let mut classifier = Classifier::new(Level::Warning);
+        let records = feed_all(
+            &mut classifier,
+            &[
+                "Traceback (most recent call last):",
+                "  File \"server.py\", line 10, in run",
+                "    start()",
+                "",
+                "    ^^^^^^^",
+                "KeyError: 'session'",
+                "",
+                "During handling of the above exception, another exception occurred:",
+                "",
+                "Traceback (most recent call last):",
+                "  File \"server.py\", line 12, in run",
+                "RuntimeError: no session",
+                "🛑 Server stopped",
+            ],
+        );
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].level, Level::Error);
+        assert_eq!(records[0].kind, Kind::Traceback { exception: "KeyError: 'session'".to_string() });
+        assert_eq!(records[0].message.lines().count(), 6);
+        assert_eq!(records[1].kind, Kind::Traceback { exception: "RuntimeError: no session".to_string() });
+        assert_eq!(records[2].level, Level::Info);
+    }
+
+    #[test]
+    fn test_flushes_unfinished_traceback() {
+        let mut classifier = Classifier::new(Level::Warning);
+        let records = feed_all(&mut classifier, &["Traceback (most recent call last):", "  File \"x.py\", line 1"]);
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, Kind::Traceback { exception: "File \"x.py\", line 1".to_string() });
+    }
+}
@@ -0,0 +1,128 @@
+// Command Line Arguments
+// Parsed on first launch and forwarded from second instances
+
+use crate::protocol::ClientMessage;
+
+/// What a launch asks the running voice shell to do
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchArgs {
+    /// Session to switch to
+    pub session: Option<String>,
+    /// Context variables to set first, e.g. the email to send results to
+    pub variables: Vec<(String, String)>,
+    /// Text command to run, as if typed into the shell
+    pub command: Option<String>,
+}
+
+impl LaunchArgs {
+    /// Parse `argv`, including the program name.
+    ///
+    /// Accepts `--session <id>`, `--command <text>`, `--var <key>=<value>`
+    /// (repeatable; all also in `--flag=value` form); bare words are joined
+    /// into the command.
+    pub fn parse<I: IntoIterator<Item = String>>(argv: I) -> Self {
+        let mut args = LaunchArgs::default();
+        let mut words = Vec::new();
+        let mut argv = argv.into_iter().skip(1);
+
+        while let Some(arg) = argv.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+
+            match flag.as_str() {
+                "--session" | "-s" => args.session = inline.or_else(|| argv.next()),
+                "--command" | "-c" => args.command = inline.or_else(|| argv.next()),
+                "--var" | "-v" => {
+                    let assignment = inline.or_else(|| argv.next()).unwrap_or_default();
+                    match assignment.split_once('=') {
+                        Some((key, value)) if !key.is_empty() => {
+                            args.variables.push((key.to_string(), value.to_string()))
+                        }
+                        _ => log::warn!("Ignoring {} {:?}: expected <key>=<value>", flag, assignment),
+                    }
+                }
+                _ if flag.starts_with('-') => log::warn!("Ignoring unknown argument: {}", arg),
+                _ => words.push(arg),
+            }
+        }
+
+        if args.command.is_none() && !words.is_empty() {
+            args.command = Some(words.join(" "));
+        }
+        args
+    }
+
+    /// Whether there is anything to forward to the backend
+    pub fn is_empty(&self) -> bool {
+        self.session.is_none() && self.command.is_none() && self.variables.is_empty()
+    }
+
+    /// Backend messages that carry out the request, in order
+    pub fn messages(&self) -> Vec<ClientMessage> {
+        let mut messages = Vec::new();
+        if let Some(ref session) = self.session {
+            messages.push(ClientMessage::SwitchSession(session.clone()));
+        }
+        for (key, value) in &self.variables {
+            messages.push(ClientMessage::SetVariable { key: key.clone(), value: value.as_str().into() });
+        }
+        if let Some(ref command) = self.command {
+            messages.push(ClientMessage::TextInput(command.clone()));
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> LaunchArgs {
+        LaunchArgs::parse(std::iter::once("voice-shell-app").chain(args.iter().copied()).map(String::from))
+    }
+
+    #[test]
+    fn test_no_arguments() {
+        assert!(parse(&[]).is_empty());
+        assert!(parse(&[]).messages().is_empty());
+    }
+
+    #[test]
+    fn test_parses_flags() {
+        let args = parse(&["--session", "s2", "--command=list files"]);
+
+        assert_eq!(args.session.as_deref(), Some("s2"));
+        assert_eq!(args.command.as_deref(), Some("list files"));
+    }
+
+    #[test]
+    fn test_bare_words_form_command() {
+        let args = parse(&["-s", "s1", "show", "disk", "usage", "--unknown"]);
+
+        assert_eq!(args.command.as_deref(), Some("show disk usage"));
+    }
+
+    #[test]
+    fn test_switches_session_before_command() {
+        let messages = parse(&["-c", "ls", "-s", "s3"]).messages();
+
+        assert_eq!(messages, [ClientMessage::SwitchSession("s3".to_string()), ClientMessage::TextInput("ls".to_string())]);
+    }
+
+    #[test]
+    fn test_sets_variables_before_command() {
+        let args = parse(&["--var", "email=tom@example.com", "--var=url=rtsp://cam", "-v", "broken", "send", "report"]);
+        let messages = args.messages();
+
+        assert_eq!(
+            messages,
+            [
+                ClientMessage::SetVariable { key: "email".to_string(), value: "tom@example.com".into() },
+                ClientMessage::SetVariable { key: "url".to_string(), value: "rtsp://cam".into() },
+                ClientMessage::TextInput("send report".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,384 @@
+// Backend WebSocket Client
+// Talks to `VoiceShellServer` over its WebSocket protocol
+
+use std::future::Future;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::launch::LaunchConfig;
+use crate::protocol::{ClientMessage, Event, Payload, ShellEvent};
+
+/// First pause before reconnecting, doubled up to `MAX_RECONNECT_DELAY`
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest pause between reconnect attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn connect(config: &LaunchConfig) -> Result<Socket, String> {
+    let (socket, _) = tokio_tungstenite::connect_async(config.ws_url().as_str())
+        .await
+        .map_err(|e| format!("WebSocket on port {} unreachable: {}", config.port, e))?;
+    Ok(socket)
+}
+
+async fn send(socket: &mut Socket, message: &serde_json::Value) -> Result<(), String> {
+    socket
+        .send(Message::Text(message.to_string()))
+        .await
+        .map_err(|e| format!("Failed to send {}: {}", message["type"], e))
+}
+
+/// Persistent connection to the backend event stream.
+///
+/// Decodes every event, fans it out to subscribers and reconnects when the
+/// connection drops. Cloning yields a handle to the same connection.
+#[derive(Clone)]
+pub struct ShellClient {
+    inner: Arc<ClientInner>,
+}
+
+struct ClientInner {
+    events: broadcast::Sender<Event>,
+    connected: watch::Sender<bool>,
+    /// Writer of the current connection
+    outgoing: StdMutex<Option<mpsc::UnboundedSender<serde_json::Value>>>,
+    /// Held for the length of an `exchange`, whose barriers must not interleave
+    exchanging: Mutex<()>,
+}
+
+impl Default for ShellClient {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(256);
+        let (connected, _) = watch::channel(false);
+        Self {
+            inner: Arc::new(ClientInner {
+                events,
+                connected,
+                outgoing: StdMutex::new(None),
+                exchanging: Mutex::new(()),
+            }),
+        }
+    }
+}
+
+impl ShellClient {
+    /// Receive decoded events from every connection
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.inner.events.subscribe()
+    }
+
+    /// Watch connections opening and closing
+    pub fn connection(&self) -> watch::Receiver<bool> {
+        self.inner.connected.subscribe()
+    }
+
+    /// Wait up to `timeout` for a connection to be open
+    pub async fn wait_connected(&self, timeout: Duration) -> Result<(), String> {
+        let mut connected = self.connection();
+        let waited = tokio::time::timeout(timeout, connected.wait_for(|connected| *connected)).await;
+        match waited {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!("Not connected to the backend after {:?}", timeout)),
+        }
+    }
+
+    /// Send a protocol message over the open connection
+    pub fn send(&self, message: &ClientMessage) -> Result<(), String> {
+        let message = serde_json::to_value(message).map_err(|e| e.to_string())?;
+        self.inner
+            .outgoing
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|outgoing| outgoing.send(message.clone()).ok())
+            .ok_or_else(|| format!("Not connected to the backend; cannot send {}", message["type"]))
+    }
+
+    /// Send `message` and collect the live events it caused.
+    ///
+    /// The server handles a connection's messages in order, so everything
+    /// it broadcasts before answering a `get_config` sent right after
+    /// `message` was caused by it, or by another client meanwhile. The
+    /// returned receiver continues after the collected events, for waiting
+    /// on work the message started.
+    pub async fn exchange(
+        &self,
+        message: &ClientMessage,
+        timeout: Duration,
+    ) -> Result<(Vec<ShellEvent>, broadcast::Receiver<Event>), String> {
+        let _turn = self.inner.exchanging.lock().await;
+        let mut events = self.subscribe();
+        self.send(message)?;
+        self.send(&ClientMessage::GetConfig)?;
+
+        let collect = async {
+            let mut caused = Vec::new();
+            loop {
+                match events.recv().await {
+                    Ok(event) if event.replayed => {}
+                    Ok(Event { payload: Payload::Known(ShellEvent::ConfigLoaded { .. }), .. }) => return Ok(caused),
+                    Ok(Event { payload: Payload::Known(event), .. }) => caused.push(event),
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        log::warn!("Missed {} backend events while waiting for a reply", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Err("Backend event stream closed".to_string()),
+                }
+            }
+        };
+        let caused = tokio::time::timeout(timeout, collect)
+            .await
+            .map_err(|_| format!("Backend did not answer within {:?}", timeout))??;
+        Ok((caused, events))
+    }
+
+    /// Stay connected for as long as `target` yields a backend to connect to.
+    ///
+    /// `target` is awaited before every attempt, so it can wait for the
+    /// backend to be (re)started and pick up new ports.
+    pub async fn run<F, Fut>(&self, mut target: F)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Option<LaunchConfig>>,
+    {
+        let mut delay = RECONNECT_DELAY;
+        while let Some(config) = target().await {
+            match self.run_once(&config).await {
+                Ok(()) => {
+                    log::info!("Backend event stream closed, reconnecting");
+                    delay = RECONNECT_DELAY;
+                }
+                Err(e) => {
+                    log::debug!("Backend event stream: {}; retrying in {:?}", e, delay);
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                }
+            }
+        }
+    }
+
+    /// Connect once and forward events until the connection closes.
+    ///
+    /// Events up to the `config_loaded` answering our first `get_config`
+    /// are the greeting and replay, and are marked as replayed.
+    pub async fn run_once(&self, config: &LaunchConfig) -> Result<(), String> {
+        let mut socket = connect(config).await?;
+        send(&mut socket, &json!({ "type": "get_config" })).await?;
+
+        let (outgoing, mut queued) = mpsc::unbounded_channel();
+        *self.inner.outgoing.lock().unwrap() = Some(outgoing);
+        self.inner.connected.send_replace(true);
+
+        let result = async {
+            let mut configs_seen = 0;
+            loop {
+                tokio::select! {
+                    message = queued.recv() => {
+                        // Only `None` once the sender is replaced by a new connection
+                        let Some(message) = message else { return Ok(()) };
+                        send(&mut socket, &message).await?;
+                    }
+                    frame = socket.next() => {
+                        let Some(frame) = frame else { return Ok(()) };
+                        let Message::Text(text) = frame.map_err(|e| e.to_string())? else {
+                            continue;
+                        };
+                        let mut event = match Event::parse(&text) {
+                            Ok(event) => event,
+                            Err(e) => {
+                                log::warn!("Ignoring malformed backend event: {}", e);
+                                continue;
+                            }
+                        };
+                        if configs_seen < 2 {
+                            if matches!(event.known(), Some(ShellEvent::ConfigLoaded { .. })) {
+                                configs_seen += 1;
+                            }
+                            event.replayed = true;
+                        }
+                        // No subscribers is fine
+                        let _ = self.inner.events.send(event);
+                    }
+                }
+            }
+        }
+        .await;
+
+        *self.inner.outgoing.lock().unwrap() = None;
+        self.inner.connected.send_replace(false);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_sends_in_order_after_greeting() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = LaunchConfig::new("en");
+        config.port = listener.local_addr().unwrap().port();
+
+        // Like `VoiceShellServer.handle_client`: the whole greeting goes out
+        // before the first message is read
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let greeting = [
+                r#"{"id": "a1", "type": "client_connected", "data": {"client_id": "c1"}}"#,
+                r#"{"type": "config_loaded", "data": {"language": "en", "email": "", "url": ""}}"#,
+                r#"{"id": "a2", "type": "context_updated", "data": {"email": "tom@example.com"}}"#,
+                r#"{"id": "old", "type": "session_output", "data": {"session_id": "s1", "line": "done"}}"#,
+            ];
+            for text in greeting {
+                socket.send(Message::Text(text.to_string())).await.unwrap();
+            }
+            let mut received = Vec::new();
+            while let Some(Ok(Message::Text(text))) = socket.next().await {
+                received.push(serde_json::from_str::<ClientMessage>(&text).unwrap());
+                if received.len() == 3 {
+                    break;
+                }
+            }
+            received
+        });
+
+        let client = ShellClient::default();
+        tokio::spawn({
+            let client = client.clone();
+            async move { client.run_once(&config).await }
+        });
+        client.wait_connected(Duration::from_secs(2)).await.unwrap();
+        client.send(&ClientMessage::SwitchSession("s2".to_string())).unwrap();
+        client.send(&ClientMessage::TextInput("ls".to_string())).unwrap();
+
+        assert_eq!(
+            server.await.unwrap(),
+            [
+                ClientMessage::GetConfig,
+                ClientMessage::SwitchSession("s2".to_string()),
+                ClientMessage::TextInput("ls".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_marks_replay_and_sends() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = LaunchConfig::new("en");
+        config.port = listener.local_addr().unwrap().port();
+
+        // Greeting and replay, then the `get_config` reply, then live events
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let greeting = [
+                r#"{"type": "config_loaded", "data": {"language": "en"}}"#,
+                r#"{"id": "old", "type": "command_executed", "data": {"command": "ls"}}"#,
+                "not json",
+            ];
+            for text in greeting {
+                socket.send(Message::Text(text.to_string())).await.unwrap();
+            }
+            let mut received = Vec::new();
+            while let Some(Ok(Message::Text(text))) = socket.next().await {
+                let message: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let reply = match message["type"].as_str().unwrap() {
+                    "get_config" => json!({ "type": "config_loaded", "data": { "language": "en" } }),
+                    _ => json!({ "id": "new", "type": "tts_speak", "data": { "text": message["content"] } }),
+                };
+                socket.send(Message::Text(reply.to_string())).await.unwrap();
+                received.push(message);
+                if received.len() == 2 {
+                    break;
+                }
+            }
+            socket.close(None).await.unwrap();
+            received
+        });
+
+        let client = ShellClient::default();
+        let mut events = client.subscribe();
+        let run = tokio::spawn({
+            let client = client.clone();
+            async move { client.run_once(&config).await }
+        });
+
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            let event = events.recv().await.unwrap();
+            seen.push((event.type_name(), event.replayed));
+        }
+        client.send(&ClientMessage::TextInput("hello".to_string())).unwrap();
+        let live = events.recv().await.unwrap();
+
+        assert_eq!(
+            seen,
+            [
+                ("config_loaded".to_string(), true),
+                ("command_executed".to_string(), true),
+                ("config_loaded".to_string(), true),
+            ]
+        );
+        assert_eq!((live.id.as_deref(), live.replayed), (Some("new"), false));
+        assert_eq!(server.await.unwrap()[1]["content"], "hello");
+        run.await.unwrap().unwrap();
+        assert!(!*client.connection().borrow());
+        assert!(client.send(&ClientMessage::Stop).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exchange_skips_replayed_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = LaunchConfig::new("en");
+        config.port = listener.local_addr().unwrap().port();
+
+        // Mimics `VoiceShellServer.handle_client`: greeting, replay, then
+        // one reply per message in order
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let greeting = [
+                r#"{"type": "config_loaded", "data": {"language": "en"}}"#,
+                r#"{"id": "old", "type": "language_changed", "data": {"language": "pl"}}"#,
+            ];
+            for text in greeting {
+                socket.send(Message::Text(text.to_string())).await.unwrap();
+            }
+            while let Some(Ok(Message::Text(text))) = socket.next().await {
+                let message: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let reply = match message["type"].as_str().unwrap() {
+                    "get_config" => json!({ "type": "config_loaded", "data": { "language": "en" } }),
+                    _ => json!({ "id": "new", "type": "language_changed", "data": { "language": message["content"] } }),
+                };
+                socket.send(Message::Text(reply.to_string())).await.unwrap();
+            }
+        });
+
+        let client = ShellClient::default();
+        tokio::spawn({
+            let client = client.clone();
+            async move { client.run_once(&config).await }
+        });
+        client.wait_connected(Duration::from_secs(2)).await.unwrap();
+
+        let (events, _) = client
+            .exchange(&ClientMessage::SetLanguage("de".to_string()), Duration::from_secs(2))
+            .await
+            .unwrap();
+
+        assert_eq!(events, [ShellEvent::LanguageChanged { language: "de".to_string() }]);
+    }
+}
@@ -1,35 +1,69 @@
 // Tauri IPC Commands
 // These functions are callable from JavaScript via invoke()
 
-use tauri::command;
+use std::time::Duration;
 
-use crate::server;
+use serde_json::{json, Map, Value};
+use tauri::{command, AppHandle, State};
+
+use crate::context;
+use crate::control::{self, CommandResult};
+use crate::logfile;
+use crate::logs::{self, Level, LogLine};
+use crate::ports::PortRequest;
+use crate::process::ShutdownResult;
+use crate::protocol::Session;
+use crate::python::{Diagnostics, Resolver};
+use crate::server::{ServerManager, ServerStatus};
+use crate::sessions::{self, SessionList};
+use crate::settings::{self, Settings, SettingsChange, SettingsStore};
+use crate::shutdown::ClosePolicy;
 
 /// Get the current server status
 #[command]
-pub async fn get_server_status() -> Result<ServerStatus, String> {
-    let is_running = server::is_server_running();
-    let port = server::get_server_port();
-    
-    Ok(ServerStatus {
-        running: is_running,
-        port,
-        url: format!("http://127.0.0.1:{}", port + 1),
-    })
+pub async fn get_server_status(server: State<'_, ServerManager>) -> Result<ServerStatus, String> {
+    Ok(server.status().await)
 }
 
-/// Restart the backend server
+/// Stop the backend server and its process group
 #[command]
-pub async fn restart_server(port: u16, language: String) -> Result<String, String> {
-    server::stop_server();
-    
-    // Wait for clean shutdown
-    std::thread::sleep(std::time::Duration::from_millis(500));
-    
-    match server::start_server(port, &language) {
-        Ok(_) => Ok("Server restarted successfully".to_string()),
-        Err(e) => Err(format!("Failed to restart server: {}", e)),
-    }
+pub async fn stop_server(server: State<'_, ServerManager>) -> Result<ShutdownResult, String> {
+    Ok(server.stop().await)
+}
+
+/// Restart the backend server and wait until it is ready.
+///
+/// Uses the configured ports, or keeps the current ones, unless new ones
+/// are given. A given language is validated and remembered, as with
+/// `set_language`; otherwise the current one is kept.
+#[command]
+pub async fn restart_server(
+    app: AppHandle,
+    server: State<'_, ServerManager>,
+    settings: State<'_, SettingsStore>,
+    ports: Option<PortRequest>,
+    language: Option<String>,
+) -> Result<RestartResult, String> {
+    let language = match language {
+        Some(language) => {
+            settings::check_language(&language)?;
+            let change = settings.update(&json!({ "language": language }))?;
+            crate::settings_changed(&app, &change);
+            language
+        }
+        None => server.language().await,
+    };
+
+    let startup = crate::restart_backend(&app, ports, &language)
+        .await
+        .map_err(|e| format!("Failed to restart server: {}", e))?;
+    let status = server.status().await;
+
+    Ok(RestartResult {
+        ws_port: status.ws_port,
+        http_port: status.http_port,
+        startup_ms: startup.as_millis() as u64,
+    })
 }
 
 /// Get application version
@@ -40,14 +74,26 @@ pub fn get_app_version() -> String {
 
 /// Get current language setting
 #[command]
-pub fn get_language() -> String {
-    server::get_language()
+pub async fn get_language(server: State<'_, ServerManager>) -> Result<String, String> {
+    Ok(server.language().await)
 }
 
-/// Set language
+/// Switch the running backend to `language` and remember it.
+///
+/// Fails for unsupported languages or if the backend does not acknowledge
+/// the change.
 #[command]
-pub async fn set_language(language: String) -> Result<String, String> {
-    server::set_language(&language);
+pub async fn set_language(
+    app: AppHandle,
+    server: State<'_, ServerManager>,
+    settings: State<'_, SettingsStore>,
+    language: String,
+) -> Result<String, String> {
+    settings::check_language(&language)?;
+    server.change_language(&language).await?;
+
+    let change = settings.update(&json!({ "language": language }))?;
+    crate::settings_changed(&app, &change);
     Ok(format!("Language set to: {}", language))
 }
 
@@ -60,14 +106,241 @@ pub fn show_notification(title: String, body: String) -> Result<(), String> {
         .icon("dialog-information")
         .show()
         .map_err(|e| e.to_string())?;
-    
+
+    Ok(())
+}
+
+/// Get what closing the main window does
+#[command]
+pub fn get_close_policy(settings: State<'_, SettingsStore>) -> ClosePolicy {
+    settings.get().close_policy
+}
+
+/// Choose between quitting and minimizing to the tray on window close
+#[command]
+pub fn set_close_policy(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    value: ClosePolicy,
+) -> Result<(), String> {
+    let change = settings.update(&json!({ "close_policy": value }))?;
+    crate::settings_changed(&app, &change);
     Ok(())
 }
 
+/// List every Python interpreter considered for the backend and why
+/// each was rejected
+#[command]
+pub async fn diagnose_python(settings: State<'_, SettingsStore>) -> Result<Diagnostics, String> {
+    let configured = settings.get().python_path;
+    tauri::async_runtime::spawn_blocking(move || Resolver::from_env(configured).resolve())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Recreate the private Python environment and restart the backend with it.
+///
+/// Progress is streamed as `bootstrap-progress` events.
+#[command]
+pub async fn repair_environment(app: AppHandle, server: State<'_, ServerManager>) -> Result<RepairResult, String> {
+    server.stop().await;
+    let interpreter = crate::bootstrap_environment(&app).await?;
+
+    let language = server.language().await;
+    let startup = crate::restart_backend(&app, None, &language).await?;
+
+    Ok(RepairResult {
+        interpreter: interpreter.display().to_string(),
+        startup_ms: startup.as_millis() as u64,
+    })
+}
+
+/// Get the persisted desktop settings
+#[command]
+pub fn get_settings(settings: State<'_, SettingsStore>) -> Settings {
+    settings.get()
+}
+
+/// Validate and save changed settings, e.g. `{ "language": "pl" }`.
+///
+/// Port, model and interpreter changes apply on the next backend restart.
+/// A new language is applied to the backend once the whole change is known
+/// to be valid, as with `set_language`, and nothing is saved if that fails.
+#[command]
+pub async fn update_settings(
+    app: AppHandle,
+    server: State<'_, ServerManager>,
+    settings: State<'_, SettingsStore>,
+    changes: serde_json::Value,
+) -> Result<SettingsChange, String> {
+    let updated = settings.preview(&changes)?;
+    if updated.language != settings.get().language {
+        server.change_language(&updated.language).await?;
+    }
+
+    let change = settings.update(&changes)?;
+    if change.changed.contains(&"log_level") {
+        logfile::set_level(&change.settings.log_level)?;
+    }
+    server
+        .configure(change.settings.python_path.clone(), change.settings.model.clone())
+        .await;
+
+    crate::settings_changed(&app, &change);
+    Ok(change)
+}
+
+/// Captured backend log lines, oldest first.
+///
+/// `since` is the `seq` of the last line already seen, `level` the lowest
+/// level to include and `limit` the number of newest lines to return.
+#[command]
+pub fn get_backend_logs(
+    server: State<'_, ServerManager>,
+    since: Option<u64>,
+    level: Option<Level>,
+    limit: Option<usize>,
+) -> Vec<LogLine> {
+    server.logs().query(since, level, limit)
+}
+
+/// Write the captured backend log to a new file in the downloads folder,
+/// for attaching to bug reports.
+///
+/// The webview shows backend content, so it does not get to pick the path.
+#[command]
+pub fn export_backend_logs(server: State<'_, ServerManager>) -> Result<ExportResult, String> {
+    let path = logs::default_export_path();
+    let lines = server.logs().export(&path)?;
+    log::info!("Exported {} backend log lines to {}", lines, path.display());
+    Ok(ExportResult { path: path.display().to_string(), lines })
+}
+
+/// Directory holding `app.log`, `backend.log` and their rotated copies
+#[command]
+pub fn get_log_dir() -> String {
+    logfile::log_dir().display().to_string()
+}
+
+/// Type `text` into the shell as if entered in the UI.
+///
+/// Returns how it was understood; a command that runs straight away is
+/// followed for up to `timeout_secs`, by default two minutes.
+#[command]
+pub async fn send_text_input(
+    server: State<'_, ServerManager>,
+    text: String,
+    timeout_secs: Option<u64>,
+) -> Result<CommandResult, String> {
+    let client = server.connected_client().await?;
+    control::send_text(client, &text, completion_timeout(timeout_secs)).await
+}
+
+/// Run the pending command and wait up to `timeout_secs` for it to finish.
+///
+/// `completed` is unset if it is still running; `0` returns once started.
+#[command]
+pub async fn confirm_command(
+    server: State<'_, ServerManager>,
+    timeout_secs: Option<u64>,
+) -> Result<CommandResult, String> {
+    let client = server.connected_client().await?;
+    control::confirm(client, completion_timeout(timeout_secs)).await
+}
+
+/// Drop the pending command
+#[command]
+pub async fn cancel_command(server: State<'_, ServerManager>) -> Result<CommandResult, String> {
+    control::cancel(server.connected_client().await?).await
+}
+
+/// Stop the process of the current session
+#[command]
+pub async fn stop_current(server: State<'_, ServerManager>) -> Result<CommandResult, String> {
+    control::stop_current(server.connected_client().await?).await
+}
+
+/// Stop the process of session `id`
+#[command]
+pub async fn stop_session(server: State<'_, ServerManager>, id: String) -> Result<CommandResult, String> {
+    control::stop_session(server.connected_client().await?, &id).await
+}
+
+/// Every conversation session and the current one
+#[command]
+pub async fn list_sessions(server: State<'_, ServerManager>) -> Result<SessionList, String> {
+    sessions::list(server.connected_client().await?).await
+}
+
+/// Start a session and make it current; unnamed ones are numbered
+#[command]
+pub async fn new_session(server: State<'_, ServerManager>, name: Option<String>) -> Result<Session, String> {
+    sessions::create(server.connected_client().await?, name).await
+}
+
+/// Make session `id` current
+#[command]
+pub async fn switch_session(server: State<'_, ServerManager>, id: String) -> Result<Session, String> {
+    sessions::switch(server.connected_client().await?, &id).await
+}
+
+/// Stop and delete session `id`; returns the remaining sessions
+#[command]
+pub async fn close_session(server: State<'_, ServerManager>, id: String) -> Result<Vec<Session>, String> {
+    sessions::close(server.connected_client().await?, &id).await
+}
+
+/// Rename session `id`
+#[command]
+pub async fn rename_session(server: State<'_, ServerManager>, id: String, name: String) -> Result<Session, String> {
+    sessions::rename(server.connected_client().await?, &id, &name).await
+}
+
+/// Every shell context variable, e.g. `email` and `url`
+#[command]
+pub fn get_context_variables(server: State<'_, ServerManager>) -> Map<String, Value> {
+    server.context().all()
+}
+
+/// One shell context variable, if set
+#[command]
+pub fn get_context_variable(server: State<'_, ServerManager>, key: String) -> Option<Value> {
+    server.context().get(&key)
+}
+
+/// Set a context variable the LLM shell uses, e.g. the email to send
+/// results to, before dictating a command
+#[command]
+pub async fn set_context_variable(server: State<'_, ServerManager>, key: String, value: String) -> Result<(), String> {
+    context::set(server.connected_client().await?, server.context(), &key, &value).await
+}
+
+/// Remove a context variable; returns whether it was set
+#[command]
+pub async fn remove_context_variable(server: State<'_, ServerManager>, key: String) -> Result<bool, String> {
+    context::remove(server.connected_client().await?, server.context(), &key).await
+}
+
+fn completion_timeout(secs: Option<u64>) -> Duration {
+    secs.map_or(control::COMPLETION_TIMEOUT, Duration::from_secs)
+}
+
 // Response types
 #[derive(serde::Serialize)]
-pub struct ServerStatus {
-    running: bool,
-    port: u16,
-    url: String,
+pub struct RestartResult {
+    ws_port: Option<u16>,
+    http_port: Option<u16>,
+    startup_ms: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct RepairResult {
+    interpreter: String,
+    startup_ms: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct ExportResult {
+    path: String,
+    lines: usize,
 }
@@ -0,0 +1,116 @@
+// Shell Context Variables
+// Local copy of the variables the backend passes to the LLM, e.g. email and url
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::{Map, Value};
+
+use crate::client::ShellClient;
+use crate::protocol::{ClientMessage, ShellEvent};
+
+/// How long the backend may take to acknowledge a change
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Context variables as last reported by the backend.
+///
+/// Kept in sync from `context_updated` and `variable_changed`, including
+/// replayed ones: they are replayed in order after the current context.
+#[derive(Default)]
+pub struct ContextCache {
+    variables: Mutex<Map<String, Value>>,
+}
+
+impl ContextCache {
+    /// Follow a context event; returns whether anything changed
+    pub fn apply(&self, event: &ShellEvent) -> bool {
+        let mut variables = self.variables.lock().unwrap();
+        match event {
+            ShellEvent::ContextUpdated(context) => {
+                if *variables == *context {
+                    return false;
+                }
+                *variables = context.clone();
+                true
+            }
+            ShellEvent::VariableChanged { key, removed: true, .. } => variables.remove(key).is_some(),
+            ShellEvent::VariableChanged { key, value, .. } => variables.insert(key.clone(), value.clone()).as_ref() != Some(value),
+            _ => false,
+        }
+    }
+
+    /// Every variable
+    pub fn all(&self) -> Map<String, Value> {
+        self.variables.lock().unwrap().clone()
+    }
+
+    /// One variable, if set
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.variables.lock().unwrap().get(key).cloned()
+    }
+}
+
+/// Set `key` for the LLM shell and wait for the backend to confirm it
+pub async fn set(client: &ShellClient, cache: &ContextCache, key: &str, value: &str) -> Result<(), String> {
+    check_key(key)?;
+    let message = ClientMessage::SetVariable { key: key.to_string(), value: Value::from(value) };
+    let changed = request(client, cache, &message, key).await?;
+    if !changed {
+        return Err(format!("Backend did not set {}", key));
+    }
+    Ok(())
+}
+
+/// Remove `key`; returns whether it was set
+pub async fn remove(client: &ShellClient, cache: &ContextCache, key: &str) -> Result<bool, String> {
+    check_key(key)?;
+    request(client, cache, &ClientMessage::RemoveVariable { key: key.to_string() }, key).await
+}
+
+/// Send `message`, apply the events it caused and report whether `key`
+/// was changed by one of them
+async fn request(client: &ShellClient, cache: &ContextCache, message: &ClientMessage, key: &str) -> Result<bool, String> {
+    let (events, _) = client.exchange(message, REPLY_TIMEOUT).await?;
+    let mut changed = false;
+    for event in &events {
+        // Also applied by `ServerManager`'s listener; applying twice is harmless
+        cache.apply(event);
+        changed |= matches!(event, ShellEvent::VariableChanged { key: changed_key, .. } if changed_key == key);
+    }
+    Ok(changed)
+}
+
+fn check_key(key: &str) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("Variable name must not be empty".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn changed(key: &str, value: Value, removed: bool) -> ShellEvent {
+        ShellEvent::VariableChanged { key: key.to_string(), value, removed }
+    }
+
+    #[test]
+    fn test_follows_context_events() {
+        let cache = ContextCache::default();
+        let context = json!({ "email": "tom@example.com", "url": "rtsp://camera" });
+        assert!(cache.apply(&ShellEvent::ContextUpdated(context.as_object().unwrap().clone())));
+        assert_eq!(cache.get("email"), Some(json!("tom@example.com")));
+
+        assert!(cache.apply(&changed("email", json!("ann@example.com"), false)));
+        assert!(!cache.apply(&changed("email", json!("ann@example.com"), false)));
+        assert_eq!(cache.get("email"), Some(json!("ann@example.com")));
+
+        assert!(cache.apply(&changed("url", Value::Null, true)));
+        assert!(!cache.apply(&changed("url", Value::Null, true)));
+        assert_eq!(cache.all().keys().collect::<Vec<_>>(), ["email"]);
+
+        assert!(!cache.apply(&ShellEvent::TtsSpeak { text: "Email set".to_string() }));
+    }
+}
@@ -0,0 +1,201 @@
+// Shell Control
+// Drives the voice shell like the web UI does and reports what came of it
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::client::ShellClient;
+use crate::protocol::{ClientMessage, CommandCompleted, CommandParsed, Event, ShellEvent};
+
+/// How long the backend may take to answer; parsing input can call the LLM
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long to wait for a started command to finish by default
+pub const COMPLETION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What the backend did in response to one control message
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CommandResult {
+    /// How text input was understood
+    pub parsed: Option<CommandParsed>,
+    /// Command that was started
+    pub executed: Option<String>,
+    /// Session the command ran in, or that was stopped
+    pub session_id: Option<String>,
+    /// Output of the started command, as far as it was waited for
+    pub output: Vec<String>,
+    /// Set once the command finished or was stopped; `None` while running
+    pub completed: Option<CommandCompleted>,
+    /// The pending command was dropped
+    pub cancelled: bool,
+    pub error: Option<String>,
+    /// Replies spoken by the shell, e.g. "Nothing to cancel"
+    pub spoken: Vec<String>,
+}
+
+impl CommandResult {
+    /// Fold in one event caused by the message
+    fn apply(&mut self, event: ShellEvent) {
+        match event {
+            ShellEvent::CommandParsed(parsed) => self.parsed = Some(parsed),
+            ShellEvent::CommandExecuted { command, session_id } => {
+                self.executed = Some(command);
+                self.session_id = session_id;
+            }
+            ShellEvent::SessionOutput { session_id, line } if self.is_ours(&session_id) => self.output.push(line),
+            ShellEvent::CommandCompleted(completed) if self.is_ours(&completed.session_id) => {
+                self.session_id = self.session_id.take().or_else(|| completed.session_id.clone());
+                self.completed = Some(completed);
+            }
+            ShellEvent::CommandError { error, session_id } if self.is_ours(&session_id) => self.error = Some(error),
+            ShellEvent::CommandCancel { .. } => self.cancelled = true,
+            ShellEvent::TtsSpeak { text } => self.spoken.push(text),
+            _ => {}
+        }
+    }
+
+    /// Whether an event about `session_id` concerns our command
+    fn is_ours(&self, session_id: &Option<String>) -> bool {
+        self.session_id.is_none() || *session_id == self.session_id
+    }
+
+    /// A command was started and has not ended yet
+    fn running(&self) -> bool {
+        self.executed.is_some() && self.completed.is_none() && self.error.is_none()
+    }
+}
+
+/// Type `text` into the shell; returns how it was understood
+pub async fn send_text(client: &ShellClient, text: &str, wait: Duration) -> Result<CommandResult, String> {
+    run(client, &ClientMessage::TextInput(text.to_string()), None, wait).await
+}
+
+/// Run the pending command and wait up to `wait` for it to finish
+pub async fn confirm(client: &ShellClient, wait: Duration) -> Result<CommandResult, String> {
+    run(client, &ClientMessage::Confirm, None, wait).await
+}
+
+/// Drop the pending command
+pub async fn cancel(client: &ShellClient) -> Result<CommandResult, String> {
+    run(client, &ClientMessage::Cancel, None, Duration::ZERO).await
+}
+
+/// Stop the current session's process
+pub async fn stop_current(client: &ShellClient) -> Result<CommandResult, String> {
+    run(client, &ClientMessage::Stop, None, Duration::ZERO).await
+}
+
+/// Stop the process of session `id`
+pub async fn stop_session(client: &ShellClient, id: &str) -> Result<CommandResult, String> {
+    run(client, &ClientMessage::StopSession(id.to_string()), Some(id), Duration::ZERO).await
+}
+
+/// Send `message` and, if it started a command, follow it for up to `wait`.
+///
+/// With a `session`, only events about that session are taken.
+async fn run(
+    client: &ShellClient,
+    message: &ClientMessage,
+    session: Option<&str>,
+    wait: Duration,
+) -> Result<CommandResult, String> {
+    let (events, mut live) = client.exchange(message, REPLY_TIMEOUT).await?;
+    let mut result = CommandResult { session_id: session.map(str::to_string), ..Default::default() };
+    events.into_iter().for_each(|event| result.apply(event));
+
+    if result.running() && !wait.is_zero() {
+        // Still running when `wait` runs out is not an error
+        let _ = tokio::time::timeout(wait, follow(&mut result, &mut live)).await;
+    }
+    Ok(result)
+}
+
+/// Apply live events until the started command ends
+async fn follow(result: &mut CommandResult, live: &mut broadcast::Receiver<Event>) {
+    while result.running() {
+        match live.recv().await {
+            Ok(event) if !event.replayed => {
+                if let Some(event) = event.known() {
+                    result.apply(event.clone());
+                }
+            }
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launch::LaunchConfig;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// Backend answering `text_input` and `confirm` like `VoiceShellServer`,
+    /// with another client's output mixed in
+    async fn fake_backend(listener: TcpListener) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let greeting = json!({ "type": "config_loaded", "data": { "language": "en" } });
+        socket.send(Message::Text(greeting.to_string())).await.unwrap();
+        while let Some(Ok(Message::Text(text))) = socket.next().await {
+            let message: serde_json::Value = serde_json::from_str(&text).unwrap();
+            let replies = match message["type"].as_str().unwrap() {
+                "get_config" => vec![json!({ "type": "config_loaded", "data": { "language": "en" } })],
+                "text_input" => vec![
+                    json!({ "type": "text_input", "data": { "text": message["content"] } }),
+                    json!({ "type": "command_parsed", "data": {
+                        "input": message["content"], "understood": true, "explanation": "List files",
+                        "command": "ls", "function": "shell", "options": null } }),
+                    json!({ "type": "tts_speak", "data": { "text": "List files. Say yes to execute." } }),
+                ],
+                "confirm" => vec![
+                    json!({ "type": "command_executed", "data": { "command": "ls", "session_id": "s2" } }),
+                    json!({ "type": "session_output", "data": { "session_id": "s1", "line": "other" } }),
+                    json!({ "type": "session_output", "data": { "session_id": "s2", "line": "README.md" } }),
+                    json!({ "type": "command_completed", "data": { "session_id": "s1", "return_code": 1 } }),
+                    json!({ "type": "command_completed", "data": { "session_id": "s2", "return_code": 0 } }),
+                ],
+                _ => vec![json!({ "type": "tts_speak", "data": { "text": "Nothing to cancel." } })],
+            };
+            for reply in replies {
+                socket.send(Message::Text(reply.to_string())).await.unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_correlates_replies() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = LaunchConfig::new("en");
+        config.port = listener.local_addr().unwrap().port();
+        tokio::spawn(fake_backend(listener));
+
+        let client = ShellClient::default();
+        tokio::spawn({
+            let client = client.clone();
+            async move { client.run_once(&config).await }
+        });
+        client.wait_connected(Duration::from_secs(2)).await.unwrap();
+
+        let parsed = send_text(&client, "list files", COMPLETION_TIMEOUT).await.unwrap();
+        let understood = parsed.parsed.unwrap();
+        assert_eq!((understood.input.as_str(), understood.command.as_deref()), ("list files", Some("ls")));
+        assert_eq!(parsed.spoken, ["List files. Say yes to execute."]);
+        assert_eq!(parsed.executed, None);
+
+        let confirmed = confirm(&client, Duration::from_secs(2)).await.unwrap();
+        assert_eq!(confirmed.session_id.as_deref(), Some("s2"));
+        assert_eq!(confirmed.output, ["README.md"]);
+        assert_eq!(confirmed.completed.unwrap().return_code, Some(0));
+
+        let cancelled = cancel(&client).await.unwrap();
+        assert!(!cancelled.cancelled);
+        assert_eq!(cancelled.spoken, ["Nothing to cancel."]);
+    }
+}
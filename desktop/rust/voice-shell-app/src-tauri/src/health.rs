@@ -0,0 +1,176 @@
+// Backend Health Checks
+// Protocol-level probes of the Python backend's HTTP and WebSocket endpoints
+
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::launch::HOST;
+
+/// Timeout for a single probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Backend health as reported by `get_server_status`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Health {
+    /// No backend process
+    Stopped,
+    /// Spawned but not answering yet
+    Starting,
+    /// Passed the last probe
+    Healthy,
+    /// Running but failed the last probe
+    Degraded { reason: String },
+}
+
+/// Header carrying the launcher token on HTTP requests
+const TOKEN_HEADER: &str = "X-Voice-Shell-Token";
+
+/// Full readiness check: the HTTP UI answers and the WebSocket greets us
+pub async fn probe(ws_port: u16, http_port: u16, token: &str) -> Result<(), String> {
+    check_http(http_port, ws_port, token).await?;
+    check_websocket(ws_port, token).await
+}
+
+/// Check that `/health` belongs to a voice shell serving `ws_port`
+pub async fn check_http(http_port: u16, ws_port: u16, token: &str) -> Result<(), String> {
+    let url = format!("http://{}:{}/health", HOST, http_port);
+
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header(TOKEN_HEADER, token)
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("{} unreachable: {}", url, e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("{} returned {}", url, response.status()));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("{} is not a voice shell: {}", url, e))?;
+
+    if body["ws_port"].as_u64() != Some(ws_port as u64) {
+        return Err(format!("{} reports WebSocket port {}", url, body["ws_port"]));
+    }
+
+    Ok(())
+}
+
+/// Connect to the WebSocket and wait for the `client_connected` or
+/// `config_loaded` event sent by `VoiceShellServer.handle_client`
+pub async fn check_websocket(ws_port: u16, token: &str) -> Result<(), String> {
+    let url = format!("ws://{}:{}/?token={}", HOST, ws_port, token);
+
+    let greeting = async {
+        let (mut socket, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| format!("WebSocket on port {} unreachable: {}", ws_port, e))?;
+
+        while let Some(message) = socket.next().await {
+            let Message::Text(text) = message.map_err(|e| e.to_string())? else {
+                continue;
+            };
+            let event: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+            if matches!(event["type"].as_str(), Some("client_connected" | "config_loaded")) {
+                let _ = socket.close(None).await;
+                return Ok(());
+            }
+        }
+
+        Err(format!("WebSocket on port {} closed before greeting", ws_port))
+    };
+
+    tokio::time::timeout(PROBE_TIMEOUT, greeting)
+        .await
+        .map_err(|_| format!("WebSocket on port {} sent no greeting within {:?}", ws_port, PROBE_TIMEOUT))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::SinkExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve a single canned HTTP response, answering 401 without `token`
+    async fn serve_once(body: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+            let status = if request.contains("x-voice-shell-token: secret") {
+                "200 OK"
+            } else {
+                "401 Unauthorized"
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+
+        port
+    }
+
+    #[tokio::test]
+    async fn test_check_http_accepts_voice_shell() {
+        let port = serve_once(r#"{"status": "ok", "ws_port": 8765}"#).await;
+        assert!(check_http(port, 8765, "secret").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_http_sends_token() {
+        let port = serve_once(r#"{"status": "ok", "ws_port": 8765}"#).await;
+        assert!(check_http(port, 8765, "wrong").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_http_rejects_foreign_service() {
+        let port = serve_once("<html>something else</html>").await;
+        assert!(check_http(port, 8765, "secret").await.is_err());
+
+        let port = serve_once(r#"{"status": "ok", "ws_port": 9000}"#).await;
+        assert!(check_http(port, 8765, "secret").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_websocket_waits_for_greeting() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            socket
+                .send(Message::Text(r#"{"type": "client_connected", "data": {}}"#.to_string()))
+                .await
+                .unwrap();
+            // Keep the connection open until the client closes it
+            while socket.next().await.is_some() {}
+        });
+
+        assert!(check_websocket(port, "secret").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_websocket_fails_without_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        assert!(check_websocket(port, "secret").await.is_err());
+    }
+}
@@ -0,0 +1,173 @@
+// Tray Indicator
+// Backend status shown by the tray menu and icon
+
+use crate::protocol::ShellEvent;
+use crate::server::BackendEvent;
+
+/// Backend state as shown in the tray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackendState {
+    Starting,
+    Running,
+    Unhealthy,
+    Restarting,
+    Stopped,
+}
+
+/// Small dot drawn over the tray icon
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Badge {
+    /// A command is running
+    Busy,
+    /// The backend is unhealthy or restarting
+    Warning,
+    /// The backend is down
+    Error,
+}
+
+impl Badge {
+    /// RGBA color of the dot
+    pub fn color(self) -> [u8; 4] {
+        match self {
+            Badge::Busy => [0x4e, 0xa8, 0xde, 0xff],
+            Badge::Warning => [0xf5, 0xa6, 0x23, 0xff],
+            Badge::Error => [0xe9, 0x45, 0x60, 0xff],
+        }
+    }
+}
+
+/// What the tray currently shows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Indicator {
+    pub backend: BackendState,
+    /// A shell command is running in the backend
+    pub busy: bool,
+}
+
+impl Default for Indicator {
+    fn default() -> Self {
+        Self { backend: BackendState::Starting, busy: false }
+    }
+}
+
+impl Indicator {
+    /// Follow a backend lifecycle event
+    pub fn apply(&mut self, event: &BackendEvent) {
+        self.backend = match event {
+            BackendEvent::Starting { .. } => BackendState::Starting,
+            BackendEvent::Ready { .. } | BackendEvent::Recovered => BackendState::Running,
+            BackendEvent::Unhealthy { .. } => BackendState::Unhealthy,
+            BackendEvent::Restarting { .. } => BackendState::Restarting,
+            BackendEvent::Failed { .. } | BackendEvent::Exited { .. } | BackendEvent::GaveUp { .. } => {
+                BackendState::Stopped
+            }
+        };
+        if self.backend != BackendState::Running && self.backend != BackendState::Unhealthy {
+            self.busy = false;
+        }
+    }
+
+    /// Follow a Voice Shell protocol event; returns true if anything changed
+    pub fn apply_shell_event(&mut self, event: &ShellEvent) -> bool {
+        let busy = match event {
+            ShellEvent::CommandExecuted { .. } => true,
+            ShellEvent::CommandCompleted(_) | ShellEvent::CommandError { .. } | ShellEvent::CommandCancel { .. } => false,
+            _ => return false,
+        };
+        std::mem::replace(&mut self.busy, busy) != busy
+    }
+
+    /// Text of the disabled status menu item
+    pub fn status_text(&self) -> String {
+        let state = match self.backend {
+            BackendState::Starting => "starting",
+            BackendState::Running => "running",
+            BackendState::Unhealthy => "unhealthy",
+            BackendState::Restarting => "restarting",
+            BackendState::Stopped => "stopped",
+        };
+        if self.busy {
+            format!("Backend: {} (command running)", state)
+        } else {
+            format!("Backend: {}", state)
+        }
+    }
+
+    /// Badge for the tray icon; problems take precedence over activity
+    pub fn badge(&self) -> Option<Badge> {
+        match self.backend {
+            BackendState::Stopped => Some(Badge::Error),
+            BackendState::Unhealthy | BackendState::Restarting => Some(Badge::Warning),
+            _ if self.busy => Some(Badge::Busy),
+            _ => None,
+        }
+    }
+}
+
+/// Copy of an RGBA icon with a dot in the bottom-right corner
+pub fn draw_badge(rgba: &[u8], width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+    let mut icon = rgba.to_vec();
+    let radius = (width.min(height) as f32) * 0.22;
+    let (cx, cy) = (width as f32 - radius, height as f32 - radius);
+
+    for y in 0..height {
+        for x in 0..width {
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                let offset = ((y * width + x) * 4) as usize;
+                icon[offset..offset + 4].copy_from_slice(&color);
+            }
+        }
+    }
+    icon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::CommandCompleted;
+
+    #[test]
+    fn test_follows_backend_events() {
+        let mut indicator = Indicator::default();
+        assert_eq!(indicator.status_text(), "Backend: starting");
+
+        indicator.apply(&BackendEvent::Ready { port: 8765, startup_ms: 10 });
+        assert_eq!(indicator.badge(), None);
+
+        indicator.apply(&BackendEvent::Unhealthy { reason: "timeout".to_string() });
+        assert_eq!(indicator.badge(), Some(Badge::Warning));
+
+        indicator.apply(&BackendEvent::GaveUp { crashes: 6, window_secs: 120 });
+        assert_eq!(indicator.status_text(), "Backend: stopped");
+        assert_eq!(indicator.badge(), Some(Badge::Error));
+    }
+
+    #[test]
+    fn test_tracks_running_command() {
+        let mut indicator = Indicator::default();
+        indicator.apply(&BackendEvent::Ready { port: 8765, startup_ms: 10 });
+
+        assert!(indicator.apply_shell_event(&ShellEvent::CommandExecuted { command: "ls".to_string(), session_id: None }));
+        assert!(!indicator.apply_shell_event(&ShellEvent::CommandOutput { line: "src".to_string() }));
+        assert_eq!(indicator.badge(), Some(Badge::Busy));
+        assert_eq!(indicator.status_text(), "Backend: running (command running)");
+
+        assert!(indicator.apply_shell_event(&ShellEvent::CommandCompleted(CommandCompleted {
+            session_id: None,
+            return_code: Some(0),
+            stopped: false,
+        })));
+        assert_eq!(indicator.badge(), None);
+    }
+
+    #[test]
+    fn test_draw_badge_marks_corner_only() {
+        let icon = vec![0u8; 16 * 16 * 4];
+        let badged = draw_badge(&icon, 16, 16, Badge::Error.color());
+
+        assert_eq!(&badged[(12 * 16 + 12) * 4..][..4], &Badge::Error.color());
+        assert_eq!(&badged[..4], &[0, 0, 0, 0]);
+        assert_eq!(badged.len(), icon.len());
+    }
+}
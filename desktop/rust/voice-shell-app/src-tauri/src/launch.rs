@@ -0,0 +1,253 @@
+// Backend Launch Contract
+// Command line shared with `streamware.voice_shell_server.main()`
+
+use std::fmt;
+use std::path::PathBuf;
+
+use rand::distributions::{Alphanumeric, DistString};
+
+use crate::python::{self, Candidate};
+
+/// Version of the launch contract.
+///
+/// Must match `LAUNCH_CONTRACT_VERSION` in `streamware/voice_shell_server.py`.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Python module that runs the backend
+pub const SERVER_MODULE: &str = "streamware.voice_shell_server";
+
+/// Environment variable carrying the auth token.
+///
+/// Must match `AUTH_TOKEN_ENV` in `streamware/voice_shell_server.py`.
+pub const TOKEN_ENV: &str = "STREAMWARE_VOICE_SHELL_TOKEN";
+
+/// Environment variable asking the backend to watch its stdin pipe and
+/// exit when the launcher dies.
+///
+/// Must match `WATCHDOG_ENV` in `streamware/voice_shell_server.py`.
+pub const WATCHDOG_ENV: &str = "STREAMWARE_VOICE_SHELL_WATCHDOG";
+
+/// The backend only ever listens on loopback
+pub const HOST: &str = "127.0.0.1";
+
+/// Exit code of argparse on a usage error
+const USAGE_EXIT_CODE: i32 = 2;
+
+/// Length of the generated auth token
+const TOKEN_LENGTH: usize = 32;
+
+/// Everything the backend is launched with
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchConfig {
+    /// Python interpreter; searched for when `None`
+    pub python: Option<PathBuf>,
+    /// WebSocket port
+    pub port: u16,
+    /// HTTP UI port
+    pub http_port: u16,
+    /// LLM model; the backend default when `None`
+    pub model: Option<String>,
+    pub language: String,
+    pub verbose: bool,
+    /// Shared secret every HTTP and WebSocket client must present.
+    /// Passed through the environment so it never shows up in `ps`.
+    pub token: String,
+}
+
+impl LaunchConfig {
+    /// Backend defaults with a fresh token; ports are zero until allocated
+    pub fn new(language: &str) -> Self {
+        Self {
+            python: None,
+            port: 0,
+            http_port: 0,
+            model: None,
+            language: language.to_string(),
+            verbose: cfg!(debug_assertions),
+            token: Alphanumeric.sample_string(&mut rand::thread_rng(), TOKEN_LENGTH),
+        }
+    }
+
+    /// Arguments after `python`
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![
+            "-m".to_string(), SERVER_MODULE.to_string(),
+            "--contract".to_string(), CONTRACT_VERSION.to_string(),
+            "--host".to_string(), HOST.to_string(),
+            "--port".to_string(), self.port.to_string(),
+            "--http-port".to_string(), self.http_port.to_string(),
+            "--lang".to_string(), self.language.clone(),
+        ];
+        if let Some(ref model) = self.model {
+            args.extend(["--model".to_string(), model.clone()]);
+        }
+        if self.verbose {
+            args.push("--verbose".to_string());
+        }
+        args
+    }
+
+    /// URL of the HTTP UI, carrying the token for the webview
+    pub fn ui_url(&self) -> String {
+        format!("http://{}:{}/?token={}", HOST, self.http_port, self.token)
+    }
+
+    /// URL of the WebSocket, carrying the token
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}:{}/?token={}", HOST, self.port, self.token)
+    }
+}
+
+/// Why the backend failed to come up
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StartupError {
+    /// No usable Python interpreter; every candidate was rejected
+    PythonNotFound { candidates: Vec<Candidate> },
+    /// A requested port is taken
+    PortUnavailable { port: u16, message: String },
+    /// The process could not be spawned
+    Spawn { message: String },
+    /// The backend rejected its command line
+    InvalidArguments { message: String },
+    /// The backend speaks a different launch contract
+    ContractMismatch { expected: u32, message: String },
+    /// The backend exited before it became ready
+    Exited { code: Option<i32>, stderr: Vec<String> },
+    /// The backend did not answer in time
+    NotReady { timeout_ms: u64, last_error: String },
+    /// A backend is already running
+    AlreadyRunning,
+    /// Stopped while starting
+    Stopped,
+    /// Crashed too often to keep restarting
+    CrashLoop { crashes: u32 },
+}
+
+impl StartupError {
+    /// Classify an exit before readiness from the exit code and stderr tail
+    pub fn from_early_exit(code: Option<i32>, stderr: &[String]) -> Self {
+        // argparse prints "usage: ..." followed by "<prog>: error: <message>"
+        let usage_error = stderr
+            .iter()
+            .rev()
+            .find_map(|line| line.split_once(": error: ").map(|(_, message)| message));
+
+        match (code, usage_error) {
+            (Some(USAGE_EXIT_CODE), Some(message)) if message.starts_with("launch contract") => {
+                StartupError::ContractMismatch {
+                    expected: CONTRACT_VERSION,
+                    message: message.to_string(),
+                }
+            }
+            (Some(USAGE_EXIT_CODE), Some(message)) => StartupError::InvalidArguments {
+                message: message.to_string(),
+            },
+            _ => StartupError::Exited {
+                code,
+                stderr: stderr.to_vec(),
+            },
+        }
+    }
+
+    /// Whether restarting with the same configuration cannot help
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, StartupError::Exited { .. } | StartupError::NotReady { .. })
+    }
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartupError::PythonNotFound { candidates } => {
+                write!(f, "No usable Python found: {}", python::report(candidates))
+            }
+            StartupError::PortUnavailable { port: 0, message } => write!(f, "No free port: {}", message),
+            StartupError::PortUnavailable { port, message } => write!(f, "Port {} is not available: {}", port, message),
+            StartupError::Spawn { message } => write!(f, "Failed to spawn Python process: {}", message),
+            StartupError::InvalidArguments { message } => write!(f, "Backend rejected its arguments: {}", message),
+            StartupError::ContractMismatch { expected, message } => {
+                write!(f, "Backend launch contract mismatch (expected {}): {}", expected, message)
+            }
+            StartupError::Exited { code: Some(code), .. } => write!(f, "Backend exited with code {} during startup", code),
+            StartupError::Exited { code: None, .. } => write!(f, "Backend was killed during startup"),
+            StartupError::NotReady { timeout_ms, last_error } => {
+                write!(f, "Server not ready after {} ms: {}", timeout_ms, last_error)
+            }
+            StartupError::AlreadyRunning => write!(f, "Server is already running"),
+            StartupError::Stopped => write!(f, "Server stopped before it became ready"),
+            StartupError::CrashLoop { crashes } => write!(f, "Server crashed {} times, giving up", crashes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stderr(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_args_cover_contract() {
+        let mut config = LaunchConfig::new("pl");
+        config.port = 9000;
+        config.http_port = 9001;
+        config.model = Some("llama3.2".to_string());
+        config.verbose = true;
+
+        assert_eq!(
+            config.args().join(" "),
+            "-m streamware.voice_shell_server --contract 1 --host 127.0.0.1 --port 9000 \
+             --http-port 9001 --lang pl --model llama3.2 --verbose"
+        );
+    }
+
+    #[test]
+    fn test_token_is_random_and_not_an_argument() {
+        let first = LaunchConfig::new("en");
+        let second = LaunchConfig::new("en");
+
+        assert_eq!(first.token.len(), TOKEN_LENGTH);
+        assert_ne!(first.token, second.token);
+        assert!(!first.args().contains(&first.token));
+        assert!(first.ui_url().ends_with(&format!("/?token={}", first.token)));
+    }
+
+    #[test]
+    fn test_classifies_argparse_error() {
+        let error = StartupError::from_early_exit(
+            Some(2),
+            &stderr(&[
+                "usage: voice_shell_server.py [-h] [--host HOST] [--port PORT]",
+                "voice_shell_server.py: error: unrecognized arguments: --lang en",
+            ]),
+        );
+
+        assert_eq!(
+            error,
+            StartupError::InvalidArguments { message: "unrecognized arguments: --lang en".to_string() }
+        );
+        assert!(error.is_fatal());
+    }
+
+    #[test]
+    fn test_classifies_contract_mismatch() {
+        let error = StartupError::from_early_exit(
+            Some(2),
+            &stderr(&["voice_shell_server.py: error: launch contract 1 not supported (expected 2)"]),
+        );
+
+        assert!(matches!(error, StartupError::ContractMismatch { expected: CONTRACT_VERSION, .. }));
+    }
+
+    #[test]
+    fn test_classifies_crash() {
+        let lines = stderr(&["Traceback (most recent call last):", "ImportError: no module"]);
+        let error = StartupError::from_early_exit(Some(1), &lines);
+
+        assert_eq!(error, StartupError::Exited { code: Some(1), stderr: lines });
+        assert!(!error.is_fatal());
+    }
+}
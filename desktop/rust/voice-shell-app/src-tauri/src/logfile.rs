@@ -0,0 +1,245 @@
+// File Logging
+// Rotating log files for the app and the Python backend
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::logs;
+use crate::settings::APP_DIR;
+
+/// Log target of forwarded backend output; written to its own file
+pub const BACKEND_TARGET: &str = "backend";
+
+/// Rotate once a file grows past this size
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotate once a file was started this long ago
+const MAX_FILE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Rotated files kept next to the current one
+const KEEP_FILES: usize = 5;
+
+/// Platform log directory, e.g. `~/.local/share/<APP_DIR>/logs` or
+/// `~/Library/Logs/<APP_DIR>`
+pub fn log_dir() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join("Library/Logs").join(APP_DIR);
+        }
+    }
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(APP_DIR)
+        .join("logs")
+}
+
+/// Parse a level name such as "info" or "debug"
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level).map_err(|_| format!("Unknown log level: {}", level))
+}
+
+/// Level set with `RUST_LOG`, which overrides the configured one
+fn env_level() -> Option<LevelFilter> {
+    std::env::var("RUST_LOG").ok().and_then(|level| parse_level(&level).ok())
+}
+
+/// Change the level of the installed logger, unless `RUST_LOG` is set
+pub fn set_level(level: &str) -> Result<(), String> {
+    let level = parse_level(level)?;
+    log::set_max_level(env_level().unwrap_or(level));
+    Ok(())
+}
+
+/// Install the file logger, writing `app.log` and `backend.log` in `dir`.
+///
+/// Logs at info level, or at `RUST_LOG`, until `set_level` is called.
+pub fn init(dir: &Path) -> Result<(), String> {
+    let logger = FileLogger {
+        app: Mutex::new(RotatingFile::new(dir, "app")),
+        backend: Mutex::new(RotatingFile::new(dir, "backend")),
+        echo: true,
+    };
+    log::set_boxed_logger(Box::new(logger)).map_err(|e| e.to_string())?;
+    log::set_max_level(env_level().unwrap_or(LevelFilter::Info));
+    Ok(())
+}
+
+/// A log file that is renamed to `<name>.1.log`, `<name>.2.log`, ... once
+/// it gets too big or too old
+pub struct RotatingFile {
+    dir: PathBuf,
+    name: String,
+    max_bytes: u64,
+    max_age: Duration,
+    keep: usize,
+    /// Open file, its size and when it was started
+    current: Option<(File, u64, SystemTime)>,
+}
+
+impl RotatingFile {
+    /// `<dir>/<name>.log` with the default limits
+    pub fn new(dir: &Path, name: &str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            name: name.to_string(),
+            max_bytes: MAX_FILE_BYTES,
+            max_age: MAX_FILE_AGE,
+            keep: KEEP_FILES,
+            current: None,
+        }
+    }
+
+    /// Path of the file being written
+    pub fn path(&self) -> PathBuf {
+        self.numbered(0)
+    }
+
+    fn numbered(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join(format!("{}.log", self.name)),
+            n => self.dir.join(format!("{}.{}.log", self.name, n)),
+        }
+    }
+
+    /// Append one line, rotating first if needed
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+        let (_, size, started) = match self.current {
+            Some(ref current) => current,
+            None => self.current.insert(self.open()?),
+        };
+        let too_old = started.elapsed().unwrap_or_default() > self.max_age;
+        if *size > 0 && (*size + len > self.max_bytes || too_old) {
+            self.rotate()?;
+        }
+
+        if let Some((ref mut file, ref mut size, _)) = self.current {
+            writeln!(file, "{}", line)?;
+            *size += len;
+        }
+        Ok(())
+    }
+
+    /// Open the current file for appending, picking up where it left off
+    fn open(&self) -> std::io::Result<(File, u64, SystemTime)> {
+        fs::create_dir_all(&self.dir)?;
+        let file = OpenOptions::new().create(true).append(true).open(self.path())?;
+        let metadata = file.metadata()?;
+        let started = metadata.created().or_else(|_| metadata.modified()).unwrap_or_else(|_| SystemTime::now());
+        Ok((file, metadata.len(), started))
+    }
+
+    /// Shift every file up by one, dropping the oldest
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.current = None;
+        let _ = fs::remove_file(self.numbered(self.keep));
+        for index in (0..self.keep).rev() {
+            let from = self.numbered(index);
+            if from.exists() {
+                fs::rename(from, self.numbered(index + 1))?;
+            }
+        }
+        // A fresh file; `created` may be the old inode's time on some systems
+        let (file, size, _) = self.open()?;
+        self.current = Some((file, size, SystemTime::now()));
+        Ok(())
+    }
+}
+
+/// `log` backend writing app and backend records to separate files
+struct FileLogger {
+    app: Mutex<RotatingFile>,
+    backend: Mutex<RotatingFile>,
+    /// Also print to stderr, as `env_logger` did
+    echo: bool,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Only warnings and errors from dependencies
+        let ours = metadata.target() == BACKEND_TARGET || metadata.target().starts_with(env!("CARGO_CRATE_NAME"));
+        metadata.level() <= log::max_level() && (ours || metadata.level() <= log::Level::Warn)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_record(record);
+        let file = if record.target() == BACKEND_TARGET { &self.backend } else { &self.app };
+        if let Err(e) = file.lock().unwrap().write_line(&line) {
+            eprintln!("Failed to write log file: {}", e);
+        }
+        if self.echo {
+            eprintln!("{}", line);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn format_record(record: &Record) -> String {
+    format!(
+        "{} {:<5} {}: {}",
+        logs::format_timestamp(logs::now_ms()),
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("voice-shell-logfile-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_keeps_limit() {
+        let dir = temp_dir("size");
+        let mut file = RotatingFile { max_bytes: 20, keep: 2, ..RotatingFile::new(&dir, "app") };
+
+        for i in 0..8 {
+            file.write_line(&format!("line number {}", i)).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(dir.join("app.log")).unwrap(), "line number 7\n");
+        assert_eq!(fs::read_to_string(dir.join("app.1.log")).unwrap(), "line number 6\n");
+        assert_eq!(fs::read_to_string(dir.join("app.2.log")).unwrap(), "line number 5\n");
+        assert!(!dir.join("app.3.log").exists());
+    }
+
+    #[test]
+    fn test_rotates_by_age_and_appends_after_restart() {
+        let dir = temp_dir("age");
+        let mut file = RotatingFile::new(&dir, "backend");
+        file.write_line("before restart").unwrap();
+
+        let mut reopened = RotatingFile::new(&dir, "backend");
+        reopened.write_line("after restart").unwrap();
+        assert_eq!(fs::read_to_string(reopened.path()).unwrap(), "before restart\nafter restart\n");
+
+        reopened.max_age = Duration::ZERO;
+        std::thread::sleep(Duration::from_millis(10));
+        reopened.write_line("next day").unwrap();
+        assert_eq!(fs::read_to_string(reopened.path()).unwrap(), "next day\n");
+        assert!(dir.join("backend.1.log").exists());
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("debug"), Ok(LevelFilter::Debug));
+        assert_eq!(parse_level("WARN"), Ok(LevelFilter::Warn));
+        assert!(parse_level("loud").is_err());
+    }
+}
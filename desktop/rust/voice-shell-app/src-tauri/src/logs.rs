@@ -0,0 +1,274 @@
+// Backend Log Buffer
+// Bounded, timestamped capture of the Python backend's output
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::broadcast;
+
+use crate::classify::{Kind, Record};
+
+/// Lines kept by `ServerManager`
+pub const DEFAULT_CAPACITY: usize = 5000;
+
+/// Which pipe a line came from
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Severity of a line, in increasing order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<Level> for log::Level {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Debug => log::Level::Debug,
+            Level::Info => log::Level::Info,
+            Level::Warning => log::Level::Warn,
+            Level::Error => log::Level::Error,
+        }
+    }
+}
+
+/// One captured line
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct LogLine {
+    /// Increases by one per line; pass as `since` to poll for newer lines
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub stream: Stream,
+    pub level: Level,
+    #[serde(flatten)]
+    pub kind: Kind,
+    pub message: String,
+}
+
+impl LogLine {
+    /// Line in the exported file
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} [{:?}] {:?}: {}",
+            format_timestamp(self.timestamp_ms),
+            self.stream,
+            self.level,
+            self.message
+        )
+    }
+}
+
+struct Lines {
+    lines: VecDeque<LogLine>,
+    next_seq: u64,
+}
+
+/// Ring buffer of backend output with live subscribers
+pub struct LogBuffer {
+    lines: Mutex<Lines>,
+    capacity: usize,
+    live: broadcast::Sender<LogLine>,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    /// Buffer keeping the last `capacity` lines
+    pub fn new(capacity: usize) -> Self {
+        let (live, _) = broadcast::channel(256);
+        Self {
+            lines: Mutex::new(Lines { lines: VecDeque::with_capacity(capacity), next_seq: 1 }),
+            capacity,
+            live,
+        }
+    }
+
+    /// Record a classified line, dropping the oldest one when full
+    pub fn push(&self, stream: Stream, record: Record) -> LogLine {
+        let line = {
+            let mut lines = self.lines.lock().unwrap();
+            let line = LogLine {
+                seq: lines.next_seq,
+                timestamp_ms: now_ms(),
+                stream,
+                level: record.level,
+                kind: record.kind,
+                message: record.message,
+            };
+            lines.next_seq += 1;
+            if lines.lines.len() == self.capacity {
+                lines.lines.pop_front();
+            }
+            lines.lines.push_back(line.clone());
+            line
+        };
+
+        // No subscribers is fine
+        let _ = self.live.send(line.clone());
+        line
+    }
+
+    /// Lines after `since` at `level` or above, keeping the newest `limit`
+    pub fn query(&self, since: Option<u64>, level: Option<Level>, limit: Option<usize>) -> Vec<LogLine> {
+        let lines = self.lines.lock().unwrap();
+        let mut matching: Vec<LogLine> = lines
+            .lines
+            .iter()
+            .filter(|line| since.is_none_or(|since| line.seq > since))
+            .filter(|line| level.is_none_or(|level| line.level >= level))
+            .cloned()
+            .collect();
+
+        if let Some(limit) = limit {
+            let skip = matching.len().saturating_sub(limit);
+            matching.drain(..skip);
+        }
+        matching
+    }
+
+    /// Receive lines as they are captured
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+        self.live.subscribe()
+    }
+
+    /// Write every buffered line to `path` as text; returns the line count
+    pub fn export(&self, path: &Path) -> Result<usize, String> {
+        let lines = self.query(None, None, None);
+        let mut text = String::new();
+        for line in &lines {
+            let _ = writeln!(text, "{}", line.to_text());
+        }
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(lines.len())
+    }
+}
+
+/// File for `export` in the downloads folder, named after the current time
+pub fn default_export_path() -> PathBuf {
+    let stamp = format_timestamp(now_ms()).replace(':', "-");
+    dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("voice-shell-backend-{}.log", &stamp[..19]))
+}
+
+/// Milliseconds since the Unix epoch
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// RFC 3339 UTC time for milliseconds since the epoch
+pub fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_newest_lines() {
+        let buffer = LogBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(Stream::Stdout, Record::text(Level::Info, &format!("line {}", i)));
+        }
+
+        let lines = buffer.query(None, None, None);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].message, "line 2");
+        assert_eq!(lines[2].seq, 5);
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let buffer = LogBuffer::new(10);
+        buffer.push(Stream::Stdout, Record::text(Level::Info, "started"));
+        buffer.push(Stream::Stderr, Record::text(Level::Warning, "slow"));
+        buffer.push(Stream::Stderr, Record::text(Level::Error, "failed"));
+        buffer.push(Stream::Stdout, Record::text(Level::Info, "retrying"));
+
+        let since = buffer.query(Some(2), None, None);
+        assert_eq!(since.iter().map(|l| l.seq).collect::<Vec<_>>(), [3, 4]);
+
+        let warnings = buffer.query(None, Some(Level::Warning), None);
+        assert_eq!(warnings.len(), 2);
+
+        let last = buffer.query(None, None, Some(1));
+        assert_eq!(last[0].message, "retrying");
+    }
+
+    #[tokio::test]
+    async fn test_live_subscribers_get_new_lines() {
+        let buffer = LogBuffer::new(10);
+        let mut live = buffer.subscribe();
+
+        buffer.push(Stream::Stderr, Record::text(Level::Error, "boom"));
+
+        assert_eq!(live.recv().await.unwrap().message, "boom");
+    }
+
+    #[test]
+    fn test_export_writes_text() {
+        let buffer = LogBuffer::new(10);
+        buffer.push(Stream::Stderr, Record::text(Level::Warning, "disk almost full"));
+
+        let path = std::env::temp_dir().join(format!("voice-shell-logs-{}.log", std::process::id()));
+        assert_eq!(buffer.export(&path).unwrap(), 1);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.trim_end().ends_with("[Stderr] Warning: disk almost full"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_timestamp(1_709_210_096_789), "2024-02-29T12:34:56.789Z");
+    }
+}
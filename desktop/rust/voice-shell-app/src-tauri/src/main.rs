@@ -6,53 +6,334 @@
     windows_subsystem = "windows"
 )]
 
+mod bootstrap;
+mod classify;
+mod cli;
+mod client;
 mod commands;
+mod context;
+mod control;
+mod health;
+mod indicator;
+mod launch;
+mod logfile;
+mod logs;
+mod ports;
+mod process;
+mod protocol;
+mod python;
 mod server;
+mod sessions;
+mod settings;
+mod shutdown;
+mod tray;
 
-use tauri::Manager;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager, RunEvent, WindowEvent};
+use tokio::sync::broadcast::error::RecvError;
+
+use bootstrap::Bootstrap;
+use cli::LaunchArgs;
+use launch::StartupError;
+use ports::PortRequest;
+use server::ServerManager;
+use settings::{SettingsChange, SettingsStore};
+use shutdown::ClosePolicy;
 
 fn main() {
-    env_logger::init();
+    if let Err(e) = logfile::init(&logfile::log_dir()) {
+        eprintln!("Failed to set up logging: {}", e);
+    }
+
+    let settings = SettingsStore::load(SettingsStore::default_path());
+    let initial = settings.get();
+    // Validated on load, so this cannot fail
+    let _ = logfile::set_level(&initial.log_level);
 
     tauri::Builder::default()
+        // Must be registered first: a second launch exits here and hands
+        // its arguments to the running instance instead
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            log::info!("Second instance launched with {:?}", argv);
+            focus_main_window(app);
+            forward_launch_args(app, LaunchArgs::parse(argv));
+        }))
         .plugin(tauri_plugin_shell::init())
-        .setup(|app| {
-            // Start Python server on app startup
-            let port = 8765;
-            let language = "en";
-            
-            log::info!("Starting Voice Shell backend server on port {}", port);
-            
-            match server::start_server(port, language) {
-                Ok(_) => {
-                    log::info!("Backend server started successfully");
-                    
-                    // Navigate to the server URL (server is already ready)
-                    if let Some(window) = app.get_webview_window("main") {
-                        let url = format!("http://127.0.0.1:{}", port + 1);
-                        log::info!("Loading UI from {}", url);
-                        
-                        let _ = window.eval(&format!(
-                            "window.location.href = '{}'",
-                            url
-                        ));
+        .manage(ServerManager::new(&initial.language))
+        .manage(settings)
+        .setup(move |app| {
+            // Start Python server on app startup; the window shows the
+            // bundled loading page until the backend answers
+            let server = app.state::<ServerManager>().inner().clone();
+            forward_backend_events(app.handle().clone(), &server);
+            forward_backend_logs(app.handle().clone(), &server);
+            forward_shell_events(app.handle().clone(), &server);
+            tray::create(app.handle(), &initial.language)?;
+            stop_on_signal(app.handle().clone());
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                log::info!("Starting Voice Shell backend server");
+                server.connect_client();
+
+                server.configure(initial.python_path.clone(), initial.model.clone()).await;
+                let ready = async {
+                    match server.start(initial.ports(), &initial.language).await {
+                        Ok(()) => {}
+                        // First run without streamware: set up a private environment
+                        Err(e @ StartupError::PythonNotFound { .. }) => {
+                            log::warn!("{}", e);
+                            bootstrap_environment(&handle).await?;
+                            server.start(initial.ports(), &initial.language).await.map_err(|e| e.to_string())?;
+                        }
+                        Err(e) => return Err(e.to_string()),
+                    }
+                    server.wait_ready().await.map_err(|e| e.to_string())
+                };
+
+                match ready.await {
+                    Ok(startup) => {
+                        log::info!("Backend server ready after {:?}", startup);
+                        load_ui(&handle, &server.ui_url().await);
+                        forward_launch_args(&handle, LaunchArgs::parse(std::env::args()));
+                    }
+                    Err(e) => {
+                        log::error!("Failed to start backend server: {}", e);
+                        show_startup_error(&handle, &e);
+
+                        // A slow first start, e.g. loading a model, may still come up
+                        if let Ok(startup) = server.wait_late_ready().await {
+                            log::info!("Backend server ready late, after {:?}", startup);
+                            load_ui(&handle, &server.ui_url().await);
+                            forward_launch_args(&handle, LaunchArgs::parse(std::env::args()));
+                        }
                     }
                 }
-                Err(e) => {
-                    log::error!("Failed to start backend server: {}", e);
+            });
+
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                let policy = window.state::<SettingsStore>().get().close_policy;
+                if policy == ClosePolicy::MinimizeToTray {
+                    api.prevent_close();
+                    let _ = window.hide();
                 }
             }
-            
-            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_server_status,
+            commands::stop_server,
             commands::restart_server,
             commands::get_app_version,
             commands::get_language,
             commands::set_language,
             commands::show_notification,
+            commands::get_close_policy,
+            commands::set_close_policy,
+            commands::get_settings,
+            commands::update_settings,
+            commands::diagnose_python,
+            commands::repair_environment,
+            commands::get_backend_logs,
+            commands::export_backend_logs,
+            commands::get_log_dir,
+            commands::send_text_input,
+            commands::confirm_command,
+            commands::cancel_command,
+            commands::stop_current,
+            commands::stop_session,
+            commands::list_sessions,
+            commands::new_session,
+            commands::switch_session,
+            commands::close_session,
+            commands::rename_session,
+            commands::get_context_variables,
+            commands::get_context_variable,
+            commands::set_context_variable,
+            commands::remove_context_variable,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                // Last chance to tear down the backend; blocks at most
+                // `ServerOptions::stop_timeout` plus the kill
+                let server = app.state::<ServerManager>().inner().clone();
+                let result = tauri::async_runtime::block_on(server.stop());
+                log::info!("Backend stopped on exit: {:?}", result.outcome);
+            }
+        });
+}
+
+/// Navigate the main window from the loading page to the backend UI.
+///
+/// The URL carries the launch token; the backend moves it into a cookie.
+fn load_ui(app: &AppHandle, url: &str) {
+    if let Some(window) = app.get_webview_window("main") {
+        log::info!("Loading backend UI");
+
+        let _ = window.eval(format!(
+            "window.location.href = '{}'",
+            url
+        ));
+    }
+}
+
+/// Restart the backend and wait until it is ready.
+///
+/// Uses `ports` if given, otherwise the configured ports, keeping the
+/// current ones where none are configured. The window follows the backend
+/// if its HTTP port moved.
+async fn restart_backend(app: &AppHandle, ports: Option<PortRequest>, language: &str) -> Result<Duration, String> {
+    let server = app.state::<ServerManager>().inner().clone();
+    let previous = server.status().await;
+    let configured = app.state::<SettingsStore>().get();
+    let ports = ports.unwrap_or(PortRequest {
+        ws: configured.ws_port.or(previous.ws_port),
+        http: configured.http_port.or(previous.http_port),
+    });
+
+    let startup = server.restart(ports, language).await?;
+
+    if server.status().await.http_port != previous.http_port {
+        load_ui(app, &server.ui_url().await);
+    }
+    Ok(startup)
+}
+
+/// Bring the main window to the front
+fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Carry out launch arguments against the backend
+fn forward_launch_args(app: &AppHandle, args: LaunchArgs) {
+    if args.is_empty() {
+        return;
+    }
+
+    let server = app.state::<ServerManager>().inner().clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = server.send(&args.messages()).await {
+            log::warn!("Failed to forward launch arguments: {}", e);
+        }
+    });
+}
+
+/// Tell the UI and the tray about saved settings
+fn settings_changed(app: &AppHandle, change: &SettingsChange) {
+    if change.changed.is_empty() {
+        return;
+    }
+    tray::settings_changed(app, &change.settings);
+    if let Err(e) = app.emit("settings-changed", change) {
+        log::warn!("Failed to emit settings-changed: {}", e);
+    }
+}
+
+/// Create the private Python environment, reporting progress to the UI
+/// and the loading page
+async fn bootstrap_environment(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let bundled = app.path().resource_dir().ok().map(|dir| dir.join("wheels"));
+
+    Bootstrap::new(bundled)
+        .run(|progress| {
+            if let Some(window) = app.get_webview_window("main") {
+                let message = serde_json::to_string(&progress.message).unwrap_or_default();
+                let _ = window.eval(format!("window.showStartupProgress && window.showStartupProgress({})", message));
+            }
+            if let Err(e) = app.emit("bootstrap-progress", &progress) {
+                log::warn!("Failed to emit bootstrap-progress: {}", e);
+            }
+        })
+        .await
+        .map_err(|e| format!("Failed to set up Python environment: {}", e))
+}
+
+/// Replace the loading message with the startup error
+fn show_startup_error(app: &AppHandle, error: &str) {
+    if let Some(window) = app.get_webview_window("main") {
+        let message = serde_json::to_string(error).unwrap_or_default();
+        let _ = window.eval(format!("window.showStartupError({})", message));
+    }
+}
+
+/// Stop the backend and quit on SIGINT/SIGTERM/SIGHUP
+fn stop_on_signal(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let signal = shutdown::termination_signal().await;
+        log::info!("Received {}, shutting down", signal);
+
+        let server = app.state::<ServerManager>().inner().clone();
+        server.stop().await;
+        app.exit(0);
+    });
+}
+
+/// Forward backend lifecycle events to the webview
+fn forward_backend_events(app: AppHandle, server: &ServerManager) {
+    let mut events = server.subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = app.emit(event.name(), &event) {
+                        log::warn!("Failed to emit {}: {}", event.name(), e);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("Dropped {} backend events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Forward decoded Voice Shell protocol events to the webview as
+/// `shell-event`
+fn forward_shell_events(app: AppHandle, server: &ServerManager) {
+    let mut events = server.client().subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = app.emit("shell-event", &event) {
+                        log::warn!("Failed to emit shell event {}: {}", event.type_name(), e);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("Dropped {} shell events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Push each captured backend log line to the webview as `backend-log`
+fn forward_backend_logs(app: AppHandle, server: &ServerManager) {
+    let mut lines = server.logs().subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match lines.recv().await {
+                Ok(line) => {
+                    let _ = app.emit("backend-log", &line);
+                }
+                // The UI can catch up with `get_backend_logs`
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
 }
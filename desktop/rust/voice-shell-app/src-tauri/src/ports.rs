@@ -0,0 +1,82 @@
+// Port Allocation
+// Picks free ports for the backend and verifies configured ones
+
+use std::net::TcpListener;
+
+use crate::launch::{StartupError, HOST};
+
+/// Ports requested for the backend; `None` picks a free port
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PortRequest {
+    /// WebSocket port
+    pub ws: Option<u16>,
+    /// HTTP UI port
+    pub http: Option<u16>,
+}
+
+impl PortRequest {
+    /// Request exactly these ports
+    pub fn fixed(ws: u16, http: u16) -> Self {
+        Self { ws: Some(ws), http: Some(http) }
+    }
+}
+
+/// Resolve a request to a distinct `(ws, http)` pair that is bindable right now.
+///
+/// Both listeners are held at once so the OS cannot hand out the same free
+/// port twice. They are released before returning, so another process could
+/// still grab a port before the backend binds it.
+pub fn allocate(request: PortRequest) -> Result<(u16, u16), StartupError> {
+    let ws = bind(request.ws)?;
+    let http = bind(request.http)?;
+
+    let ws_port = ws.local_addr().map_err(unavailable(request.ws))?.port();
+    let http_port = http.local_addr().map_err(unavailable(request.http))?.port();
+
+    Ok((ws_port, http_port))
+}
+
+fn bind(port: Option<u16>) -> Result<TcpListener, StartupError> {
+    TcpListener::bind((HOST, port.unwrap_or(0))).map_err(unavailable(port))
+}
+
+fn unavailable(port: Option<u16>) -> impl Fn(std::io::Error) -> StartupError {
+    move |e| StartupError::PortUnavailable {
+        port: port.unwrap_or(0),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocates_distinct_free_ports() {
+        let (ws, http) = allocate(PortRequest::default()).unwrap();
+
+        assert_ne!(ws, 0);
+        assert_ne!(http, 0);
+        assert_ne!(ws, http);
+    }
+
+    #[test]
+    fn test_keeps_requested_ports() {
+        let (free, _) = allocate(PortRequest::default()).unwrap();
+        let (ws, http) = allocate(PortRequest { ws: Some(free), http: None }).unwrap();
+
+        assert_eq!(ws, free);
+        assert_ne!(http, free);
+    }
+
+    #[test]
+    fn test_rejects_busy_port() {
+        let busy = TcpListener::bind((HOST, 0)).unwrap();
+        let port = busy.local_addr().unwrap().port();
+
+        assert!(matches!(
+            allocate(PortRequest { ws: None, http: Some(port) }),
+            Err(StartupError::PortUnavailable { port: p, .. }) if p == port
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+// Backend Process Group
+// Spawns the backend in its own group and tears the whole group down
+
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use tokio::process::{Child, Command};
+
+use crate::launch::WATCHDOG_ENV;
+
+/// How a shutdown ended
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownOutcome {
+    /// There was no backend process
+    NotRunning,
+    /// The backend had already exited on its own
+    AlreadyExited,
+    /// The backend exited after SIGTERM within the grace period
+    Graceful,
+    /// The backend ignored SIGTERM and was killed
+    Killed,
+    /// The backend could not be waited for
+    Failed,
+}
+
+/// Structured result of stopping the backend
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ShutdownResult {
+    pub outcome: ShutdownOutcome,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub elapsed_ms: u64,
+    /// Leftover processes in the group (e.g. shell commands) were killed
+    pub killed_stragglers: bool,
+}
+
+impl ShutdownResult {
+    /// Result for a manager with no process
+    pub fn not_running() -> Self {
+        Self {
+            outcome: ShutdownOutcome::NotRunning,
+            code: None,
+            signal: None,
+            elapsed_ms: 0,
+            killed_stragglers: false,
+        }
+    }
+}
+
+/// Put the spawned process at the head of a new process group, so commands
+/// it runs can be signalled along with it
+pub fn isolate(command: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        command.process_group(0)
+    }
+
+    #[cfg(windows)]
+    {
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP)
+    }
+}
+
+/// Make the spawned process die with the launcher, even on SIGKILL.
+///
+/// The child gets a stdin pipe whose write end stays in the `Child` and is
+/// never written; the backend exits on EOF (see `WATCHDOG_ENV`). On Linux
+/// it is also sent SIGTERM by the kernel when the spawning thread exits.
+pub fn tie_to_parent(command: &mut Command) -> &mut Command {
+    command.stdin(Stdio::piped()).env(WATCHDOG_ENV, "stdin");
+
+    #[cfg(target_os = "linux")]
+    {
+        let parent = std::process::id() as libc::pid_t;
+        // Only async-signal-safe calls between fork and exec
+        unsafe {
+            command.pre_exec(move || {
+                if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                // The launcher may have died before prctl took effect
+                if libc::getppid() != parent {
+                    libc::_exit(1);
+                }
+                Ok(())
+            });
+        }
+    }
+
+    command
+}
+
+/// Send `signal` to every process in the group led by `pid`.
+///
+/// Returns false if the group no longer exists.
+#[cfg(unix)]
+pub fn signal_group(pid: u32, signal: i32) -> bool {
+    unsafe { libc::killpg(pid as libc::pid_t, signal) == 0 }
+}
+
+/// Kill whatever is left of the group led by `pid`
+pub fn kill_group(pid: Option<u32>) -> bool {
+    #[cfg(unix)]
+    {
+        pid.is_some_and(|pid| signal_group(pid, libc::SIGKILL))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// SIGTERM the group, wait up to `grace`, then SIGKILL it.
+///
+/// The group is killed even after a graceful exit so that no grandchildren
+/// outlive the backend.
+pub async fn terminate(child: &mut Child, grace: Duration) -> ShutdownResult {
+    let started = Instant::now();
+    let pid = child.id();
+
+    let (outcome, status) = match child.try_wait() {
+        Ok(Some(status)) => (ShutdownOutcome::AlreadyExited, Ok(status)),
+        _ => {
+            request_exit(child, pid);
+            match tokio::time::timeout(grace, child.wait()).await {
+                Ok(status) => (ShutdownOutcome::Graceful, status),
+                Err(_) => {
+                    log::warn!("Python server ignored SIGTERM for {:?}, killing it", grace);
+                    kill_group(pid);
+                    let _ = child.start_kill();
+                    (ShutdownOutcome::Killed, child.wait().await)
+                }
+            }
+        }
+    };
+
+    let killed_stragglers = kill_group(pid);
+
+    match status {
+        Ok(status) => {
+            let (code, signal) = exit_details(&status);
+            ShutdownResult {
+                outcome,
+                code,
+                signal,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+                killed_stragglers,
+            }
+        }
+        Err(e) => {
+            log::error!("Error waiting for Python server: {}", e);
+            ShutdownResult {
+                outcome: ShutdownOutcome::Failed,
+                code: None,
+                signal: None,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+                killed_stragglers,
+            }
+        }
+    }
+}
+
+/// Ask the process group to shut down
+fn request_exit(child: &mut Child, pid: Option<u32>) {
+    #[cfg(unix)]
+    {
+        let _ = child;
+        if let Some(pid) = pid {
+            signal_group(pid, libc::SIGTERM);
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        // No SIGTERM on Windows; the grace period only covers the kill
+        let _ = pid;
+        let _ = child.start_kill();
+    }
+}
+
+/// Exit code and terminating signal of a finished process
+pub fn exit_details(status: &ExitStatus) -> (Option<i32>, Option<i32>) {
+    #[cfg(unix)]
+    let signal = std::os::unix::process::ExitStatusExt::signal(status);
+    #[cfg(not(unix))]
+    let signal = None;
+
+    (status.code(), signal)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn spawn_sh(script: &str) -> Child {
+        isolate(Command::new("sh").args(["-c", script])).spawn().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_terminate_gracefully() {
+        let mut child = spawn_sh("sleep 30");
+        let result = terminate(&mut child, Duration::from_secs(5)).await;
+
+        assert_eq!(result.outcome, ShutdownOutcome::Graceful);
+        assert_eq!(result.signal, Some(libc::SIGTERM));
+    }
+
+    #[tokio::test]
+    async fn test_terminate_escalates_to_kill() {
+        let mut child = spawn_sh("trap '' TERM; while true; do sleep 0.1; done");
+        // Let the shell install its trap
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let result = terminate(&mut child, Duration::from_millis(300)).await;
+
+        assert_eq!(result.outcome, ShutdownOutcome::Killed);
+        assert_eq!(result.signal, Some(libc::SIGKILL));
+    }
+
+    #[tokio::test]
+    async fn test_terminate_kills_grandchildren() {
+        // The grandchild ignores SIGTERM and leaves a marker if it survives
+        let marker = std::env::temp_dir().join(format!("voice-shell-orphan-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+        let mut child = spawn_sh(&format!(
+            "sh -c \"trap '' TERM; sleep 0.5; touch {}\" & wait",
+            marker.display()
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let result = terminate(&mut child, Duration::from_secs(5)).await;
+
+        assert_eq!(result.outcome, ShutdownOutcome::Graceful);
+        assert!(result.killed_stragglers);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(!marker.exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_parent_death_signal_follows_spawning_thread() {
+        // PR_SET_PDEATHSIG fires when the spawning *thread* exits, not the
+        // process, which is why the backend is only spawned from runtime
+        // worker threads
+        let handle = tokio::runtime::Handle::current();
+        let mut child = std::thread::spawn(move || {
+            let _runtime = handle.enter();
+            tie_to_parent(Command::new("sleep").arg("30")).spawn().unwrap()
+        })
+        .join()
+        .unwrap();
+
+        let status = tokio::time::timeout(Duration::from_secs(5), child.wait()).await.unwrap().unwrap();
+        assert_eq!(exit_details(&status).1, Some(libc::SIGTERM));
+    }
+
+    #[tokio::test]
+    async fn test_terminate_reports_already_exited() {
+        let mut child = spawn_sh("exit 3");
+        child.wait().await.unwrap();
+
+        let result = terminate(&mut child, Duration::from_secs(1)).await;
+
+        assert_eq!(result.outcome, ShutdownOutcome::AlreadyExited);
+        assert_eq!(result.code, Some(3));
+    }
+}
@@ -0,0 +1,438 @@
+// Voice Shell Protocol
+// Typed messages to and events from `VoiceShellServer`.
+// Events mirror `voice_shell_events.EventType`, messages the `msg_type`
+// branches of `VoiceShellInputMixin.handle_message`. Recorded samples of
+// both live in `protocol/` and are checked by the tests below.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Message sent to the backend as `{"type": ..., "content": ...}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "content", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Start a session, optionally named
+    NewSession(Option<String>),
+    SwitchSession(String),
+    CloseSession(String),
+    RenameSession { id: String, name: String },
+    /// Answered with `sessions_list`, or `session_created` if there are none
+    GetSessions,
+    /// Recognised speech from the browser
+    VoiceInput(String),
+    TextInput(String),
+    /// Run the pending command
+    Confirm,
+    /// Drop the pending command
+    Cancel,
+    /// Stop the current session's process
+    Stop,
+    StopSession(String),
+    SetLanguage(String),
+    /// Answered with `config_loaded`
+    GetConfig,
+    SetVariable { key: String, value: Value },
+    RemoveVariable { key: String },
+}
+
+/// Lifecycle of a session's process
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Idle,
+    Pending,
+    Running,
+    Completed,
+    Error,
+    Stopped,
+}
+
+/// One conversation session, as in `Session.to_dict()`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Session {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub command: String,
+    pub status: SessionStatus,
+    /// Lines of output kept by the backend
+    #[serde(default)]
+    pub output_lines: usize,
+    /// ISO 8601 local time
+    pub created_at: String,
+    #[serde(default)]
+    pub has_process: bool,
+}
+
+/// How the backend understood an input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CommandParsed {
+    pub input: String,
+    pub understood: bool,
+    #[serde(default)]
+    pub explanation: String,
+    /// Shell command awaiting confirmation
+    pub command: Option<String>,
+    pub function: Option<String>,
+    /// `(key, description)` choices of a clarification question
+    pub options: Option<Vec<(String, String)>>,
+}
+
+/// A session's process finished or was stopped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CommandCompleted {
+    pub session_id: Option<String>,
+    pub return_code: Option<i32>,
+    /// Ended by a stop request
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub stopped: bool,
+}
+
+/// Event payload, tagged by the `type` the backend sends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ShellEvent {
+    VoiceInput {
+        text: String,
+        source: Option<String>,
+    },
+    TextInput {
+        text: String,
+    },
+    CommandConfirm {},
+    SessionCreated {
+        session: Session,
+        sessions: Vec<Session>,
+    },
+    SessionSwitched {
+        session: Session,
+        /// Last lines of the session's output
+        #[serde(default)]
+        output: Vec<String>,
+        sessions: Vec<Session>,
+    },
+    SessionClosed {
+        session_id: String,
+        sessions: Vec<Session>,
+    },
+    SessionRenamed {
+        session: Session,
+        sessions: Vec<Session>,
+    },
+    SessionOutput {
+        session_id: Option<String>,
+        line: String,
+    },
+    /// Reply to `get_sessions`; not an `EventType`
+    SessionsList {
+        sessions: Vec<Session>,
+        current: Option<String>,
+        #[serde(default)]
+        output: Vec<String>,
+    },
+    CommandCancel {
+        command: Option<String>,
+    },
+    CommandParsed(CommandParsed),
+    CommandExecuted {
+        command: String,
+        session_id: Option<String>,
+    },
+    CommandOutput {
+        line: String,
+    },
+    CommandError {
+        error: String,
+        session_id: Option<String>,
+    },
+    CommandCompleted(CommandCompleted),
+    TtsSpeak {
+        text: String,
+    },
+    TtsComplete {},
+    /// The whole shell context
+    ContextUpdated(Map<String, Value>),
+    LanguageChanged {
+        language: String,
+    },
+    ConfigLoaded {
+        language: String,
+        #[serde(default)]
+        email: String,
+        #[serde(default)]
+        url: String,
+    },
+    VariableChanged {
+        key: String,
+        value: Value,
+        #[serde(default, skip_serializing_if = "is_false")]
+        removed: bool,
+    },
+    ClientConnected {
+        client_id: String,
+    },
+    ClientDisconnected {
+        client_id: String,
+    },
+}
+
+/// A known event, or one this app cannot decode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Payload {
+    Known(ShellEvent),
+    Unknown {
+        #[serde(rename = "type")]
+        kind: String,
+        #[serde(default)]
+        data: Value,
+    },
+}
+
+/// One message from the backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Event {
+    /// Event id; missing on direct replies such as `config_loaded`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(flatten)]
+    pub payload: Payload,
+    /// Sent as part of the greeting and replay of a new connection rather
+    /// than as it happened
+    #[serde(default, skip_deserializing)]
+    pub replayed: bool,
+}
+
+impl Event {
+    /// Decode one WebSocket text frame
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let event: Event = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if let Payload::Unknown { ref kind, .. } = event.payload {
+            log::debug!("Undecoded backend event {}: {}", kind, text);
+        }
+        Ok(event)
+    }
+
+    /// The decoded event, if known
+    pub fn known(&self) -> Option<&ShellEvent> {
+        match self.payload {
+            Payload::Known(ref event) => Some(event),
+            Payload::Unknown { .. } => None,
+        }
+    }
+
+    /// The protocol `type`, e.g. "command_parsed"
+    pub fn type_name(&self) -> String {
+        match self.payload {
+            Payload::Known(ref event) => event.type_name(),
+            Payload::Unknown { ref kind, .. } => kind.clone(),
+        }
+    }
+}
+
+impl ShellEvent {
+    /// The protocol `type`, e.g. "command_parsed"
+    pub fn type_name(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value["type"].as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    const EVENTS: &str = include_str!("../protocol/events.jsonl");
+    const MESSAGES: &str = include_str!("../protocol/messages.jsonl");
+
+    /// Event types the backend defines but never sends
+    const UNSENT_EVENTS: [&str; 2] = ["command_confirm", "tts_complete"];
+
+    /// Drop `null` members, which the backend treats like missing ones
+    fn without_nulls(value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter().filter(|(_, v)| !v.is_null()).map(|(k, v)| (k, without_nulls(v))).collect(),
+            ),
+            Value::Array(items) => Value::Array(items.into_iter().map(without_nulls).collect()),
+            other => other,
+        }
+    }
+
+    fn fixtures(jsonl: &str) -> impl Iterator<Item = Value> + '_ {
+        jsonl.lines().filter(|line| !line.trim().is_empty()).map(|line| serde_json::from_str(line).unwrap())
+    }
+
+    /// Source of a backend module, read from `VOICE_SHELL_STREAMWARE_DIR` or,
+    /// by default, the `streamware` package of the monorepo checkout.
+    ///
+    /// `None` only when `VOICE_SHELL_SKIP_PY_PROTOCOL` is set, so the drift
+    /// check cannot pass without checking anything.
+    fn python_source(name: &str) -> Option<String> {
+        let dir = match std::env::var_os("VOICE_SHELL_STREAMWARE_DIR") {
+            Some(dir) => dir.into(),
+            None => Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../streamware"),
+        };
+        let path = dir.join(name);
+        match std::fs::read_to_string(&path) {
+            Ok(source) => Some(source),
+            Err(_) if std::env::var_os("VOICE_SHELL_SKIP_PY_PROTOCOL").is_some() => None,
+            Err(e) => panic!(
+                "{}: {}; set VOICE_SHELL_STREAMWARE_DIR, or VOICE_SHELL_SKIP_PY_PROTOCOL=1 to skip",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    /// Values of `NAME = "value"` in `class EventType`
+    fn python_event_types(source: &str) -> Vec<&str> {
+        let class = source.split("class EventType").nth(1).unwrap();
+        let body = class.split("\n\n\n").next().unwrap();
+        body.lines()
+            .filter_map(|line| line.split_once(" = \"").map(|(_, value)| value.trim_end_matches('"')))
+            .collect()
+    }
+
+    /// Message types handled by `handle_message`
+    fn python_message_types(source: &str) -> Vec<&str> {
+        source
+            .split("msg_type == \"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .collect()
+    }
+
+    #[test]
+    fn test_event_fixtures_round_trip() {
+        for fixture in fixtures(EVENTS) {
+            let event: Event = serde_json::from_value(fixture.clone()).unwrap();
+            assert!(event.known().is_some(), "undecoded fixture: {}", fixture);
+
+            let mut encoded = serde_json::to_value(&event).unwrap();
+            encoded.as_object_mut().unwrap().remove("replayed");
+            assert_eq!(without_nulls(encoded), without_nulls(fixture));
+        }
+    }
+
+    #[test]
+    fn test_message_fixtures_round_trip() {
+        for fixture in fixtures(MESSAGES) {
+            let message: ClientMessage = serde_json::from_value(fixture.clone()).unwrap();
+            assert_eq!(without_nulls(serde_json::to_value(&message).unwrap()), fixture);
+        }
+    }
+
+    #[test]
+    fn test_matches_python_protocol() {
+        let (Some(events), Some(input)) = (python_source("voice_shell_events.py"), python_source("voice_shell_input.py"))
+        else {
+            return;
+        };
+
+        let recorded: HashSet<String> = fixtures(EVENTS).map(|e| e["type"].as_str().unwrap().to_string()).collect();
+        let event_types = python_event_types(&events);
+        assert!(event_types.len() >= 20);
+        for event_type in event_types {
+            assert!(
+                recorded.contains(event_type) || UNSENT_EVENTS.contains(&event_type),
+                "no recorded {} event in protocol/events.jsonl",
+                event_type
+            );
+            let decoded = serde_json::from_value::<ShellEvent>(json!({ "type": event_type, "data": {} }));
+            assert!(!decoded.is_err_and(|e| e.to_string().contains("unknown variant")), "no ShellEvent::{}", event_type);
+        }
+
+        let recorded: HashSet<String> = fixtures(MESSAGES).map(|m| m["type"].as_str().unwrap().to_string()).collect();
+        let message_types = python_message_types(&input);
+        assert!(message_types.len() >= 10);
+        for message_type in message_types {
+            assert!(recorded.contains(message_type), "no recorded {} message in protocol/messages.jsonl", message_type);
+        }
+    }
+
+    /// JSON Schemas of the protocol, by file name under `protocol/`
+    fn schemas() -> [(&'static str, Value); 2] {
+        let schema = |root| serde_json::to_value(root).unwrap_or_default();
+        [
+            ("events.schema.json", schema(schemars::schema_for!(Event))),
+            ("messages.schema.json", schema(schemars::schema_for!(ClientMessage))),
+        ]
+    }
+
+    /// Run with `UPDATE_PROTOCOL_SCHEMA=1` to regenerate the schema files
+    #[test]
+    fn test_schema_files_are_current() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("protocol");
+        for (name, schema) in schemas() {
+            let path = dir.join(name);
+            let text = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+            if std::env::var_os("UPDATE_PROTOCOL_SCHEMA").is_some() {
+                std::fs::write(&path, &text).unwrap();
+            }
+            let current = std::fs::read_to_string(&path).unwrap_or_default();
+            assert!(current == text, "{} is out of date; rerun with UPDATE_PROTOCOL_SCHEMA=1", path.display());
+        }
+    }
+
+    #[test]
+    fn test_decodes_known_events() {
+        let event = Event::parse(
+            r#"{"id": "a1b2c3d4", "type": "command_completed", "timestamp": "2024-05-01T10:00:00",
+                "data": {"session_id": "s2", "return_code": 0}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(event.id.as_deref(), Some("a1b2c3d4"));
+        assert_eq!(event.type_name(), "command_completed");
+        assert_eq!(
+            event.known(),
+            Some(&ShellEvent::CommandCompleted(CommandCompleted {
+                session_id: Some("s2".to_string()),
+                return_code: Some(0),
+                stopped: false
+            }))
+        );
+
+        let config = Event::parse(r#"{"type": "config_loaded", "data": {"language": "pl", "email": "", "url": ""}}"#).unwrap();
+        assert_eq!(config.id, None);
+        assert!(matches!(config.known(), Some(ShellEvent::ConfigLoaded { language, .. }) if language == "pl"));
+    }
+
+    #[test]
+    fn test_keeps_unknown_events() {
+        let event = Event::parse(r#"{"type": "camera_frame", "data": {"fps": 5}}"#).unwrap();
+
+        assert_eq!(event.known(), None);
+        assert_eq!(event.type_name(), "camera_frame");
+        assert_eq!(event.payload, Payload::Unknown { kind: "camera_frame".to_string(), data: json!({ "fps": 5 }) });
+    }
+
+    #[test]
+    fn test_serializes_in_wire_format() {
+        let event = Event {
+            id: Some("e1".to_string()),
+            timestamp: None,
+            payload: Payload::Known(ShellEvent::TtsSpeak { text: "Executing command.".to_string() }),
+            replayed: true,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "id": "e1", "type": "tts_speak", "data": { "text": "Executing command." }, "replayed": true })
+        );
+    }
+}
@@ -0,0 +1,304 @@
+// Python Interpreter Discovery
+// Ordered search for an interpreter that can run the backend
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::bootstrap;
+use crate::launch::SERVER_MODULE;
+
+/// Oldest supported Python; matches `requires-python` in `pyproject.toml`
+pub const MIN_VERSION: (u32, u32) = (3, 8);
+
+/// Prints the version, then "ok" or the import error
+const PROBE_SCRIPT: &str = "\
+import sys
+print('%d.%d.%d' % sys.version_info[:3])
+try:
+    import streamware.voice_shell_server
+    print('ok')
+except BaseException as e:
+    print('%s: %s' % (type(e).__name__, e))
+";
+
+/// Where a candidate came from, in search order
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    /// `python_path` in the settings
+    Config,
+    /// The active virtualenv
+    VirtualEnv,
+    /// The active conda environment
+    Conda,
+    /// A `.venv` in the working directory or one of its parents
+    ProjectVenv,
+    /// The private environment created by `bootstrap`
+    Managed,
+    /// `PATH`, including pyenv shims
+    Path,
+}
+
+/// One interpreter that was considered
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Candidate {
+    pub source: Source,
+    pub path: PathBuf,
+    /// Reported Python version, if it ran
+    pub version: Option<String>,
+    /// Why it cannot run the backend; `None` if usable
+    pub rejected: Option<String>,
+}
+
+/// Every candidate in search order and the one that was picked
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Diagnostics {
+    pub selected: Option<PathBuf>,
+    pub candidates: Vec<Candidate>,
+}
+
+/// Inputs to the search, normally taken from the environment
+#[derive(Debug, Clone, Default)]
+pub struct Resolver {
+    pub configured: Option<PathBuf>,
+    pub virtual_env: Option<PathBuf>,
+    pub conda_prefix: Option<PathBuf>,
+    pub working_dir: Option<PathBuf>,
+    pub managed: Option<PathBuf>,
+    pub path: Option<OsString>,
+}
+
+impl Resolver {
+    /// Search with the process environment and an optional configured path
+    pub fn from_env(configured: Option<PathBuf>) -> Self {
+        Self {
+            configured,
+            virtual_env: std::env::var_os("VIRTUAL_ENV").map(PathBuf::from),
+            conda_prefix: std::env::var_os("CONDA_PREFIX").map(PathBuf::from),
+            working_dir: std::env::current_dir().ok(),
+            managed: Some(bootstrap::venv_dir()),
+            path: std::env::var_os("PATH"),
+        }
+    }
+
+    /// Existing interpreter files in search order, without duplicates
+    pub fn candidates(&self) -> Vec<(Source, PathBuf)> {
+        let mut candidates = Vec::new();
+
+        if let Some(ref configured) = self.configured {
+            candidates.push((Source::Config, configured.clone()));
+        }
+        if let Some(ref venv) = self.virtual_env {
+            candidates.extend(env_interpreters(venv).map(|p| (Source::VirtualEnv, p)));
+        }
+        if let Some(ref prefix) = self.conda_prefix {
+            candidates.extend(env_interpreters(prefix).map(|p| (Source::Conda, p)));
+        }
+        if let Some(venv) = self.working_dir.as_deref().and_then(find_project_venv) {
+            candidates.extend(env_interpreters(&venv).map(|p| (Source::ProjectVenv, p)));
+        }
+        if let Some(ref venv) = self.managed {
+            candidates.extend(env_interpreters(venv).map(|p| (Source::Managed, p)));
+        }
+        if let Some(ref path) = self.path {
+            for dir in std::env::split_paths(path) {
+                candidates.extend(names().iter().map(|name| (Source::Path, dir.join(name))));
+            }
+        }
+
+        // The configured path is reported even if missing; others only if present
+        let mut seen = HashSet::new();
+        candidates
+            .into_iter()
+            .filter(|(source, path)| *source == Source::Config || path.is_file())
+            .filter(|(_, path)| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())))
+            .collect()
+    }
+
+    /// Check candidates in order and stop at the first usable one
+    pub fn resolve(&self) -> Diagnostics {
+        let mut diagnostics = Diagnostics { selected: None, candidates: Vec::new() };
+
+        for (source, path) in self.candidates() {
+            let candidate = check(source, &path);
+            let usable = candidate.rejected.is_none();
+            diagnostics.candidates.push(candidate);
+            if usable {
+                diagnostics.selected = Some(path);
+                break;
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Summary of why `candidates` were rejected
+pub fn report(candidates: &[Candidate]) -> String {
+    if candidates.is_empty() {
+        return "no Python interpreter on PATH or in an active environment".to_string();
+    }
+    candidates
+        .iter()
+        .filter_map(|c| {
+            c.rejected
+                .as_ref()
+                .map(|reason| format!("{} ({:?}): {}", c.path.display(), c.source, reason))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Run the probe script with `path` and judge the result
+pub fn check(source: Source, path: &Path) -> Candidate {
+    let mut candidate = Candidate { source, path: path.to_path_buf(), version: None, rejected: None };
+
+    let output = match Command::new(path).args(["-c", PROBE_SCRIPT]).output() {
+        Ok(output) => output,
+        Err(e) => {
+            candidate.rejected = Some(format!("cannot run: {}", e));
+            return candidate;
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+
+    let Some(version) = lines.next().and_then(parse_version) else {
+        candidate.rejected = Some(format!("not a Python interpreter (exit status {})", output.status));
+        return candidate;
+    };
+    candidate.version = Some(format!("{}.{}.{}", version.0, version.1, version.2));
+
+    candidate.rejected = if !is_supported(version) {
+        Some(format!("Python {}.{} or newer required", MIN_VERSION.0, MIN_VERSION.1))
+    } else {
+        match lines.next() {
+            Some("ok") => None,
+            Some(error) => Some(format!("cannot import {}: {}", SERVER_MODULE, error)),
+            None => Some(format!("cannot import {}", SERVER_MODULE)),
+        }
+    };
+    candidate
+}
+
+/// Whether a `(major, minor, patch)` version can run the backend
+pub fn is_supported(version: (u32, u32, u32)) -> bool {
+    (version.0, version.1) >= MIN_VERSION
+}
+
+/// Parse "3.11.4" into its parts
+pub fn parse_version(line: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = line.trim().split('.').map(|part| part.parse().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+/// Interpreter executables to look for, preferred first
+fn names() -> &'static [&'static str] {
+    if cfg!(windows) {
+        &["python.exe"]
+    } else {
+        &["python3", "python"]
+    }
+}
+
+/// Interpreters inside a virtualenv or conda prefix, preferred first
+pub fn env_interpreters(prefix: &Path) -> impl Iterator<Item = PathBuf> + '_ {
+    let dirs = if cfg!(windows) { vec![prefix.join("Scripts"), prefix.to_path_buf()] } else { vec![prefix.join("bin")] };
+    dirs.into_iter().flat_map(|dir| names().iter().map(move |name| dir.join(name)))
+}
+
+/// Nearest `.venv` in `dir` or its ancestors
+fn find_project_venv(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().map(|d| d.join(".venv")).find(|venv| venv.is_dir())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Directory for this test, emptied first
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("voice-shell-python-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Fake interpreter printing `output` regardless of its arguments
+    fn fake_python(path: &Path, output: &str) -> PathBuf {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, format!("#!/bin/sh\nprintf '{}'\n", output)).unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_path_buf()
+    }
+
+    #[test]
+    fn test_candidates_in_search_order() {
+        let dir = temp_dir("order");
+        let venv = fake_python(&dir.join("venv/bin/python"), "");
+        let conda = fake_python(&dir.join("conda/bin/python3"), "");
+        let project = fake_python(&dir.join("project/.venv/bin/python3"), "");
+        let managed = fake_python(&dir.join("managed/bin/python3"), "");
+        let on_path = fake_python(&dir.join("path/python3"), "");
+
+        let resolver = Resolver {
+            configured: Some(dir.join("missing/python")),
+            virtual_env: Some(dir.join("venv")),
+            conda_prefix: Some(dir.join("conda")),
+            working_dir: Some(dir.join("project/src")),
+            managed: Some(dir.join("managed")),
+            // The venv's bin on PATH too, as after `activate`
+            path: Some(std::env::join_paths([dir.join("venv/bin"), dir.join("path")]).unwrap()),
+        };
+
+        assert_eq!(
+            resolver.candidates(),
+            vec![
+                (Source::Config, dir.join("missing/python")),
+                (Source::VirtualEnv, venv),
+                (Source::Conda, conda),
+                (Source::ProjectVenv, project),
+                (Source::Managed, managed),
+                (Source::Path, on_path),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejects_old_and_broken_interpreters() {
+        let dir = temp_dir("reject");
+        let old = fake_python(&dir.join("old"), "3.6.9\\nok\\n");
+        let broken = fake_python(&dir.join("broken"), "3.12.1\\nModuleNotFoundError: No module named streamware\\n");
+        let good = fake_python(&dir.join("good"), "3.11.4\\nok\\n");
+
+        let old = check(Source::Path, &old);
+        assert_eq!(old.version.as_deref(), Some("3.6.9"));
+        assert!(old.rejected.unwrap().contains("3.8 or newer"));
+
+        let broken = check(Source::Path, &broken);
+        assert!(broken.rejected.unwrap().contains("No module named streamware"));
+
+        assert_eq!(check(Source::Config, &good).rejected, None);
+        assert!(check(Source::Config, &dir.join("missing")).rejected.unwrap().starts_with("cannot run"));
+    }
+
+    #[test]
+    fn test_resolve_reports_every_rejection() {
+        let dir = temp_dir("resolve");
+        fake_python(&dir.join("venv/bin/python3"), "3.7.0\\nok\\n");
+        let good = fake_python(&dir.join("path/python3"), "3.10.0\\nok\\n");
+
+        let resolver = Resolver {
+            virtual_env: Some(dir.join("venv")),
+            path: Some(dir.join("path").into_os_string()),
+            ..Resolver::default()
+        };
+        let diagnostics = resolver.resolve();
+
+        assert_eq!(diagnostics.selected, Some(good));
+        assert_eq!(diagnostics.candidates.len(), 2);
+        assert!(report(&diagnostics.candidates).contains("VirtualEnv"));
+    }
+}
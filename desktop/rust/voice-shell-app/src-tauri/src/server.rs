@@ -1,199 +1,1061 @@
 // Python Server Management
 // Handles starting, stopping, and monitoring the Python backend
 
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
-use std::io::{BufRead, BufReader};
-use std::thread;
-
-lazy_static::lazy_static! {
-    static ref SERVER_PROCESS: Arc<Mutex<Option<Child>>> = Arc::new(Mutex::new(None));
-    static ref SERVER_PORT: Arc<Mutex<u16>> = Arc::new(Mutex::new(8765));
-    static ref SERVER_LANGUAGE: Arc<Mutex<String>> = Arc::new(Mutex::new("en".to_string()));
-}
-
-/// Start the Python Voice Shell server
-pub fn start_server(port: u16, language: &str) -> Result<(), String> {
-    // Check if already running
-    if is_server_running() {
-        return Err("Server is already running".to_string());
-    }
-    
-    // Store configuration
-    *SERVER_PORT.lock().unwrap() = port;
-    *SERVER_LANGUAGE.lock().unwrap() = language.to_string();
-    
-    // Find Python executable
-    let python = find_python().ok_or("Python not found")?;
-    
-    log::info!("Starting Python server with: {} -m streamware.voice_shell_server", python);
-    
-    // Start the Python process
-    let child = Command::new(&python)
-        .args([
-            "-m", "streamware.voice_shell_server",
-            "--port", &port.to_string(),
-            "--lang", language,
-        ])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn Python process: {}", e))?;
-    
-    let pid = child.id();
-    log::info!("Python server started with PID: {}", pid);
-    
-    // Store the process handle
-    *SERVER_PROCESS.lock().unwrap() = Some(child);
-    
-    // Start log forwarding thread
-    start_log_forwarder();
-    
-    // Wait for server to be ready (check HTTP port)
-    let http_port = port + 1;
-    let max_attempts = 30;  // 30 seconds timeout
-    for attempt in 1..=max_attempts {
-        if check_server_ready(http_port) {
-            log::info!("Server ready on port {} after {} seconds", http_port, attempt);
-            return Ok(());
-        }
-        std::thread::sleep(std::time::Duration::from_secs(1));
-        log::debug!("Waiting for server... attempt {}/{}", attempt, max_attempts);
-    }
-    
-    log::warn!("Server may not be fully ready after {} seconds", max_attempts);
-    Ok(())
-}
-
-/// Check if the HTTP server is responding
-fn check_server_ready(port: u16) -> bool {
-    use std::net::TcpStream;
-    use std::time::Duration;
-    
-    let addr = format!("127.0.0.1:{}", port);
-    TcpStream::connect_timeout(
-        &addr.parse().unwrap(),
-        Duration::from_millis(500)
-    ).is_ok()
-}
-
-/// Stop the Python server
-pub fn stop_server() {
-    if let Some(mut child) = SERVER_PROCESS.lock().unwrap().take() {
-        log::info!("Stopping Python server...");
-        
-        // Try graceful shutdown first
-        #[cfg(unix)]
-        {
-            unsafe {
-                libc::kill(child.id() as i32, libc::SIGTERM);
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{broadcast, watch, Mutex};
+
+use crate::classify::{Classifier, Record};
+use crate::client::ShellClient;
+use crate::context::ContextCache;
+use crate::health::{self, Health};
+use crate::launch::{LaunchConfig, StartupError, HOST, TOKEN_ENV};
+use crate::logfile::BACKEND_TARGET;
+use crate::logs::{Level, LogBuffer, Stream};
+use crate::ports::{self, PortRequest};
+use crate::process::{self, ShutdownOutcome, ShutdownResult};
+use crate::protocol::{ClientMessage, ShellEvent};
+use crate::python;
+
+/// Default conversation language
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// How often the supervisor polls the backend process
+const SUPERVISOR_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Pause between readiness probes
+const READY_PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Pause between liveness checks of a ready backend
+const LIVENESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long the backend gets to acknowledge a language change
+const LANGUAGE_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a ready backend may take to accept the client's connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Stderr lines kept to explain a failed startup
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Owns one Python backend process and its configuration.
+///
+/// Registered with `app.manage()` and injected into commands as `tauri::State`.
+/// Cloning is cheap and yields a handle to the same backend; separately
+/// constructed instances are fully independent of each other.
+#[derive(Clone)]
+pub struct ServerManager {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    state: Mutex<ServerState>,
+    options: ServerOptions,
+    events: broadcast::Sender<BackendEvent>,
+    readiness: watch::Sender<Readiness>,
+    /// Output of every backend process this manager spawned
+    logs: Arc<LogBuffer>,
+    /// Event stream of whichever backend is running
+    client: ShellClient,
+    /// Shell context variables, kept in sync by `connect_client`
+    context: ContextCache,
+}
+
+struct ServerState {
+    process: Option<Child>,
+    /// Stderr tail of the current process
+    stderr: Arc<StderrTail>,
+    /// Configuration of the current launch, with allocated ports
+    config: LaunchConfig,
+    /// Interpreter resolved by the last `start`, reused for restarts
+    interpreter: Option<PathBuf>,
+    health: Health,
+    /// Bumped on every start/stop so a stale supervisor knows to exit
+    generation: u64,
+    /// Bumped on every spawn/stop so stale probes know to exit
+    launch: u64,
+}
+
+impl ServerState {
+    fn is_running(&mut self) -> bool {
+        if let Some(ref mut child) = self.process {
+            // Try to get exit status without blocking
+            match child.try_wait() {
+                Ok(Some(_)) => false, // Process has exited
+                Ok(None) => true,     // Process is still running
+                Err(_) => false,      // Error checking status
             }
-        }
-        
-        #[cfg(windows)]
-        {
-            let _ = child.kill();
-        }
-        
-        // Wait for process to exit
-        match child.wait() {
-            Ok(status) => log::info!("Python server exited with: {}", status),
-            Err(e) => log::error!("Error waiting for Python server: {}", e),
+        } else {
+            false
         }
     }
 }
 
-/// Check if server is running
-pub fn is_server_running() -> bool {
-    if let Some(ref mut child) = *SERVER_PROCESS.lock().unwrap() {
-        // Try to get exit status without blocking
-        match child.try_wait() {
-            Ok(Some(_)) => false, // Process has exited
-            Ok(None) => true,     // Process is still running
-            Err(_) => false,      // Error checking status
+/// Tunables for a `ServerManager`
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// Crash restart policy of the supervisor
+    pub restart: RestartPolicy,
+    /// How long to wait for the HTTP port to answer after spawning
+    pub ready_timeout: Duration,
+    /// How long the backend gets to exit after SIGTERM before SIGKILL
+    pub stop_timeout: Duration,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            restart: RestartPolicy::default(),
+            ready_timeout: Duration::from_secs(30),
+            stop_timeout: Duration::from_secs(5),
         }
-    } else {
-        false
     }
 }
 
-/// Get current server port
-pub fn get_server_port() -> u16 {
-    *SERVER_PORT.lock().unwrap()
+/// Readiness of the current backend process
+#[derive(Debug, Clone, PartialEq)]
+pub enum Readiness {
+    /// No backend has been started
+    Stopped,
+    /// Spawned, waiting for the HTTP port to answer
+    Pending,
+    /// Answering, with the measured startup time
+    Ready(Duration),
+    /// Did not become ready
+    Failed(StartupError),
+}
+
+/// Last stderr lines of one backend process
+#[derive(Default)]
+struct StderrTail {
+    lines: StdMutex<VecDeque<String>>,
+    /// Set once stderr reaches EOF
+    closed: watch::Sender<bool>,
 }
 
-/// Get current language
-pub fn get_language() -> String {
-    SERVER_LANGUAGE.lock().unwrap().clone()
+impl StderrTail {
+    fn push(&self, line: &str) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == STDERR_TAIL_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_string());
+    }
+
+    /// Lines captured so far, after giving the reader a moment to drain
+    async fn drain(&self) -> Vec<String> {
+        let mut closed = self.closed.subscribe();
+        let _ = tokio::time::timeout(Duration::from_secs(1), closed.wait_for(|c| *c)).await;
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
 }
 
-/// Set language
-pub fn set_language(language: &str) {
-    *SERVER_LANGUAGE.lock().unwrap() = language.to_string();
+impl Default for ServerManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_LANGUAGE)
+    }
 }
 
-/// Find Python executable
-fn find_python() -> Option<String> {
-    // Try common Python paths
-    let candidates = [
-        "python3",
-        "python",
-        "/usr/bin/python3",
-        "/usr/local/bin/python3",
-    ];
-    
-    for candidate in candidates {
-        if Command::new(candidate)
-            .arg("--version")
-            .output()
-            .is_ok()
-        {
-            return Some(candidate.to_string());
+impl ServerManager {
+    /// Create a manager with no backend running yet
+    pub fn new(language: &str) -> Self {
+        Self::with_options(language, ServerOptions::default())
+    }
+
+    /// Create a manager with custom restart and readiness settings
+    pub fn with_options(language: &str, options: ServerOptions) -> Self {
+        let (events, _) = broadcast::channel(64);
+        let (readiness, _) = watch::channel(Readiness::Stopped);
+
+        Self {
+            inner: Arc::new(Inner {
+                state: Mutex::new(ServerState {
+                    process: None,
+                    stderr: Arc::default(),
+                    config: LaunchConfig::new(language),
+                    interpreter: None,
+                    health: Health::Stopped,
+                    generation: 0,
+                    launch: 0,
+                }),
+                options,
+                events,
+                readiness,
+                logs: Arc::default(),
+                client: ShellClient::default(),
+                context: ContextCache::default(),
+            }),
+        }
+    }
+
+    /// Subscribe to backend lifecycle events
+    pub fn subscribe(&self) -> broadcast::Receiver<BackendEvent> {
+        self.inner.events.subscribe()
+    }
+
+    /// Captured backend output
+    pub fn logs(&self) -> &LogBuffer {
+        &self.inner.logs
+    }
+
+    /// Protocol connection to the backend; see `connect_client`
+    pub fn client(&self) -> &ShellClient {
+        &self.inner.client
+    }
+
+    /// Context variables as last reported by the backend
+    pub fn context(&self) -> &ContextCache {
+        &self.inner.context
+    }
+
+    /// `client` once the backend is ready and connected
+    pub async fn connected_client(&self) -> Result<&ShellClient, String> {
+        self.wait_ready().await.map_err(|e| e.to_string())?;
+        self.inner.client.wait_connected(CONNECT_TIMEOUT).await?;
+        Ok(&self.inner.client)
+    }
+
+    /// Keep `client` connected to the backend across restarts and crashes,
+    /// and `context` in sync with it
+    pub fn connect_client(&self) {
+        let mut events = self.inner.client.subscribe();
+        let server = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if let Some(event) = event.known() {
+                            server.inner.context.apply(event);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        log::warn!("Context may be stale; missed {} backend events", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let server = self.clone();
+        tokio::spawn(async move {
+            let target = || async {
+                let mut readiness = server.inner.readiness.subscribe();
+                readiness.wait_for(|r| matches!(r, Readiness::Ready(_))).await.ok()?;
+                Some(server.inner.state.lock().await.config.clone())
+            };
+            server.inner.client.run(target).await;
+        });
+    }
+
+    fn emit(&self, event: BackendEvent) {
+        // No subscribers is fine, e.g. in tests
+        let _ = self.inner.events.send(event);
+    }
+
+    /// Spawn the Python Voice Shell server.
+    ///
+    /// Returns as soon as the process is running; use `wait_ready` to
+    /// wait for it to answer.
+    pub async fn start(&self, ports: PortRequest, language: &str) -> Result<(), StartupError> {
+        let configured = {
+            let mut state = self.inner.state.lock().await;
+            if state.is_running() {
+                return Err(StartupError::AlreadyRunning);
+            }
+            state.config.python.clone()
+        };
+
+        // Find an interpreter that can import the backend; this runs every
+        // candidate, so keep it off the runtime and outside the state lock
+        let diagnostics = tokio::task::spawn_blocking(move || python::Resolver::from_env(configured).resolve())
+            .await
+            .map_err(|e| StartupError::Spawn { message: e.to_string() })?;
+
+        let mut state = self.inner.state.lock().await;
+
+        // Check if already running
+        if state.is_running() {
+            return Err(StartupError::AlreadyRunning);
+        }
+
+        // Store configuration
+        let (ws_port, http_port) = ports::allocate(ports)?;
+        state.config.port = ws_port;
+        state.config.http_port = http_port;
+        state.config.language = language.to_string();
+
+        let Some(interpreter) = diagnostics.selected else {
+            let error = StartupError::PythonNotFound { candidates: diagnostics.candidates };
+            self.inner.readiness.send_replace(Readiness::Failed(error.clone()));
+            return Err(error);
+        };
+        state.interpreter = Some(interpreter.clone());
+
+        // Start the Python process
+        let (child, stderr) = spawn_backend(&state.config, &interpreter, &self.inner.logs)?;
+        state.generation += 1;
+        self.launched(&mut state, child, stderr);
+
+        // Watch the process and restart it if it crashes
+        tokio::spawn(self.clone().supervise(state.generation));
+
+        Ok(())
+    }
+
+    /// Stop the backend, start it again and wait until it is ready
+    pub async fn restart(&self, ports: PortRequest, language: &str) -> Result<Duration, String> {
+        self.stop().await;
+        self.start(ports, language).await.map_err(|e| e.to_string())?;
+        self.wait_ready().await.map_err(|e| e.to_string())
+    }
+
+    /// Record a freshly spawned process and start probing it
+    fn launched(&self, state: &mut ServerState, child: Child, stderr: Arc<StderrTail>) {
+        self.emit(BackendEvent::Starting {
+            port: state.config.port,
+            pid: child.id().unwrap_or_default(),
+        });
+        state.process = Some(child);
+        state.stderr = stderr;
+        state.health = Health::Starting;
+        state.launch += 1;
+
+        self.inner.readiness.send_replace(Readiness::Pending);
+        tokio::spawn(self.clone().probe_ready(state.launch, state.config.clone(), Instant::now()));
+    }
+
+    /// Resolves once the backend answers, or fails after the ready timeout
+    pub async fn wait_ready(&self) -> Result<Duration, StartupError> {
+        let mut readiness = self.inner.readiness.subscribe();
+        let result = readiness
+            .wait_for(|r| *r != Readiness::Pending)
+            .await
+            .map_err(|_| StartupError::Stopped)?
+            .clone();
+
+        match result {
+            Readiness::Ready(startup) => Ok(startup),
+            Readiness::Failed(error) => Err(error),
+            Readiness::Stopped | Readiness::Pending => Err(StartupError::Stopped),
         }
     }
-    
-    // Try to find in virtual environment
-    if let Ok(venv) = std::env::var("VIRTUAL_ENV") {
-        let venv_python = format!("{}/bin/python", venv);
-        if std::path::Path::new(&venv_python).exists() {
-            return Some(venv_python);
+
+    /// Wait for a backend that missed the ready timeout to answer after all.
+    ///
+    /// Resolves once readiness leaves a non-fatal `Failed`; `Ok` only for a
+    /// late start of the same launch.
+    pub async fn wait_late_ready(&self) -> Result<Duration, StartupError> {
+        let mut readiness = self.inner.readiness.subscribe();
+        let result = readiness
+            .wait_for(|r| !matches!(r, Readiness::Failed(error) if !error.is_fatal()))
+            .await
+            .map_err(|_| StartupError::Stopped)?
+            .clone();
+
+        match result {
+            Readiness::Ready(startup) => Ok(startup),
+            Readiness::Failed(error) => Err(error),
+            Readiness::Stopped | Readiness::Pending => Err(StartupError::Stopped),
         }
     }
-    
-    None
-}
 
-/// Forward Python process logs
-fn start_log_forwarder() {
-    if let Some(ref mut child) = *SERVER_PROCESS.lock().unwrap() {
-        // Forward stdout
-        if let Some(stdout) = child.stdout.take() {
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines().filter_map(|l| l.ok()) {
-                    log::info!("[Python] {}", line);
+    /// Probe the backend until it answers and report ready or failed
+    async fn probe_ready(self, launch: u64, config: LaunchConfig, started: Instant) {
+        let (port, http_port) = (config.port, config.http_port);
+        let timeout = self.inner.options.ready_timeout;
+        let mut last_error = String::new();
+
+        // Ok when ready; Err(Some(code)) when the process exited first,
+        // Err(None) when stopped or restarted by someone else
+        let probe = async {
+            loop {
+                {
+                    let mut state = self.inner.state.lock().await;
+                    if state.launch != launch {
+                        return Err(None);
+                    }
+                    if let Some(Ok(Some(status))) = state.process.as_mut().map(|c| c.try_wait()) {
+                        return Err(Some(status));
+                    }
+                }
+                match health::probe(port, http_port, &config.token).await {
+                    Ok(()) => return Ok(()),
+                    Err(e) => last_error = e,
+                }
+                tokio::time::sleep(READY_PROBE_INTERVAL).await;
+            }
+        };
+
+        let error = match tokio::time::timeout(timeout, probe).await {
+            Ok(Ok(())) => {
+                let startup = started.elapsed();
+                log::info!("Server ready on port {} after {:?}", http_port, startup);
+
+                let mut state = self.inner.state.lock().await;
+                if state.launch == launch {
+                    state.health = Health::Healthy;
+                    self.inner.readiness.send_replace(Readiness::Ready(startup));
+                    self.emit(BackendEvent::Ready {
+                        port,
+                        startup_ms: startup.as_millis() as u64,
+                    });
+                    tokio::spawn(self.clone().monitor(launch, config, started));
+                }
+                return;
+            }
+            // Stopped or restarted while probing; that path reports its own state
+            Ok(Err(None)) => return,
+            Ok(Err(Some(status))) => {
+                let stderr = self.inner.state.lock().await.stderr.clone();
+                StartupError::from_early_exit(status.code(), &stderr.drain().await)
+            }
+            Err(_) => StartupError::NotReady {
+                timeout_ms: timeout.as_millis() as u64,
+                last_error,
+            },
+        };
+
+        log::error!("Python server failed to start: {}", error);
+
+        let mut state = self.inner.state.lock().await;
+        if state.launch != launch {
+            return;
+        }
+        state.health = Health::Degraded { reason: error.to_string() };
+        self.inner.readiness.send_replace(Readiness::Failed(error.clone()));
+        if error.is_fatal() {
+            // Restarting with the same command line cannot help
+            state.generation += 1;
+            state.launch += 1;
+            state.process = None;
+            state.health = Health::Stopped;
+        } else {
+            // Keep watching in case the backend comes up late; `monitor`
+            // then reports it ready after all
+            tokio::spawn(self.clone().monitor(launch, config, started));
+        }
+        drop(state);
+
+        self.emit(BackendEvent::Failed { error });
+    }
+
+    /// Periodically check a backend and report health changes.
+    ///
+    /// Also watches one that missed the ready timeout, and publishes it as
+    /// ready once it answers.
+    async fn monitor(self, launch: u64, config: LaunchConfig, started: Instant) {
+        loop {
+            let late = matches!(*self.inner.readiness.borrow(), Readiness::Failed(ref error) if !error.is_fatal());
+            tokio::time::sleep(if late { READY_PROBE_INTERVAL } else { LIVENESS_INTERVAL }).await;
+
+            let result = health::check_http(config.http_port, config.port, &config.token).await;
+
+            let mut state = self.inner.state.lock().await;
+            if state.launch != launch {
+                return;
+            }
+            if late && result.is_ok() {
+                let startup = started.elapsed();
+                log::info!("Server ready on port {} after {:?}, past the ready timeout", config.http_port, startup);
+                state.health = Health::Healthy;
+                self.inner.readiness.send_replace(Readiness::Ready(startup));
+                self.emit(BackendEvent::Ready {
+                    port: config.port,
+                    startup_ms: startup.as_millis() as u64,
+                });
+                continue;
+            }
+            match (result, &state.health) {
+                (Ok(()), Health::Healthy) => {}
+                (Ok(()), _) => {
+                    log::info!("Python server is healthy again");
+                    state.health = Health::Healthy;
+                    self.emit(BackendEvent::Recovered);
+                }
+                (Err(reason), Health::Degraded { .. }) => {
+                    state.health = Health::Degraded { reason };
                 }
+                (Err(reason), _) => {
+                    log::warn!("Python server failed liveness check: {}", reason);
+                    state.health = Health::Degraded { reason: reason.clone() };
+                    self.emit(BackendEvent::Unhealthy { reason });
+                }
+            }
+        }
+    }
+
+    /// Stop the Python server and its process group.
+    ///
+    /// Sends SIGTERM, escalating to SIGKILL after `stop_timeout`.
+    pub async fn stop(&self) -> ShutdownResult {
+        let child = {
+            let mut state = self.inner.state.lock().await;
+            // Detach the supervisor and probes before the process goes away
+            state.generation += 1;
+            state.launch += 1;
+            state.health = Health::Stopped;
+            state.process.take()
+        };
+        self.inner.readiness.send_replace(Readiness::Stopped);
+
+        let Some(mut child) = child else {
+            return ShutdownResult::not_running();
+        };
+
+        log::info!("Stopping Python server...");
+
+        let result = process::terminate(&mut child, self.inner.options.stop_timeout).await;
+        log::info!("Python server stopped: {:?}", result);
+
+        if result.outcome != ShutdownOutcome::Failed {
+            self.emit(BackendEvent::Exited {
+                code: result.code,
+                signal: result.signal,
+                expected: true,
             });
         }
-        
-        // Forward stderr
-        if let Some(stderr) = child.stderr.take() {
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines().filter_map(|l| l.ok()) {
-                    log::warn!("[Python] {}", line);
+        result
+    }
+
+    /// Watch the process spawned for `generation` and restart it on crashes
+    async fn supervise(self, generation: u64) {
+        let policy = &self.inner.options.restart;
+        let mut crashes: VecDeque<Instant> = VecDeque::new();
+
+        loop {
+            tokio::time::sleep(SUPERVISOR_INTERVAL).await;
+
+            {
+                let mut state = self.inner.state.lock().await;
+                if state.generation != generation {
+                    return; // Stopped or restarted by someone else
+                }
+                let Some(child) = state.process.as_mut() else {
+                    return;
+                };
+                let pid = child.id();
+                match child.try_wait() {
+                    Ok(None) => continue,
+                    Ok(Some(status)) => {
+                        log::warn!("Python server exited unexpectedly: {}", status);
+                        // Don't leave its shell commands running
+                        process::kill_group(pid);
+                        state.health = Health::Degraded { reason: format!("Exited with {}", status) };
+                        self.emit(BackendEvent::exited(&status, false));
+                    }
+                    Err(e) => {
+                        log::warn!("Lost track of Python server: {}", e);
+                        self.emit(BackendEvent::Exited { code: None, signal: None, expected: false });
+                    }
                 }
+            }
+
+            let now = Instant::now();
+            crashes.push_back(now);
+            while crashes.front().is_some_and(|t| now.duration_since(*t) > policy.window) {
+                crashes.pop_front();
+            }
+
+            let attempt = crashes.len() as u32;
+            if attempt > policy.max_crashes {
+                log::error!(
+                    "Python server crashed {} times within {:?}, giving up",
+                    attempt, policy.window
+                );
+                self.give_up(generation, attempt).await;
+                return;
+            }
+
+            let delay = policy.backoff(attempt);
+            log::info!("Restarting Python server in {:?} (attempt {})", delay, attempt);
+            self.emit(BackendEvent::Restarting {
+                attempt,
+                delay_ms: delay.as_millis() as u64,
             });
+            tokio::time::sleep(delay).await;
+
+            let mut state = self.inner.state.lock().await;
+            if state.generation != generation {
+                return;
+            }
+            // Keep the ports the webview already points at
+            let ports = PortRequest::fixed(state.config.port, state.config.http_port);
+            let Some(interpreter) = state.interpreter.clone() else {
+                return;
+            };
+            let spawned = ports::allocate(ports)
+                .and_then(|_| spawn_backend(&state.config, &interpreter, &self.inner.logs));
+            match spawned {
+                Ok((child, stderr)) => self.launched(&mut state, child, stderr),
+                Err(e) => {
+                    log::error!("Failed to restart Python server: {}", e);
+                    drop(state);
+                    self.give_up(generation, attempt).await;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Stop supervising after too many crashes
+    async fn give_up(&self, generation: u64, crashes: u32) {
+        let mut state = self.inner.state.lock().await;
+        if state.generation != generation {
+            return;
+        }
+        // Detach the probes of the last launch too
+        state.launch += 1;
+        state.process = None;
+        state.health = Health::Stopped;
+        drop(state);
+
+        self.inner.readiness.send_replace(Readiness::Failed(StartupError::CrashLoop { crashes }));
+        self.emit(BackendEvent::GaveUp {
+            crashes,
+            window_secs: self.inner.options.restart.window.as_secs(),
+        });
+    }
+
+    /// Snapshot of the backend state
+    pub async fn status(&self) -> ServerStatus {
+        let mut state = self.inner.state.lock().await;
+        let running = state.is_running();
+        let config = &state.config;
+        // Ports are zero until the first start allocates them
+        let (ws_port, http_port) = match config.port {
+            0 => (None, None),
+            _ => (Some(config.port), Some(config.http_port)),
+        };
+
+        ServerStatus {
+            running,
+            ws_port,
+            http_port,
+            ws_url: ws_port.map(|port| format!("ws://{}:{}", HOST, port)),
+            http_url: http_port.map(|port| format!("http://{}:{}", HOST, port)),
+            health: if running { state.health.clone() } else { Health::Stopped },
+        }
+    }
+
+    /// URL the webview loads, including the auth token
+    pub async fn ui_url(&self) -> String {
+        self.inner.state.lock().await.config.ui_url()
+    }
+
+    /// Send protocol messages in order over `client` once the backend is
+    /// ready and connected
+    pub async fn send(&self, messages: &[ClientMessage]) -> Result<(), String> {
+        let client = self.connected_client().await?;
+        messages.iter().try_for_each(|message| client.send(message))
+    }
+
+    /// Check if server is running
+    pub async fn is_running(&self) -> bool {
+        self.inner.state.lock().await.is_running()
+    }
+
+    /// Get current language
+    pub async fn language(&self) -> String {
+        self.inner.state.lock().await.config.language.clone()
+    }
+
+    /// Set language
+    pub async fn set_language(&self, language: &str) {
+        self.inner.state.lock().await.config.language = language.to_string();
+    }
+
+    /// Switch the running backend to `language` and wait for its
+    /// `language_changed` acknowledgement; a stopped backend just picks it
+    /// up on the next start
+    pub async fn change_language(&self, language: &str) -> Result<(), String> {
+        if self.is_running().await {
+            let message = ClientMessage::SetLanguage(language.to_string());
+            let (events, _) = self
+                .connected_client()
+                .await?
+                .exchange(&message, LANGUAGE_ACK_TIMEOUT)
+                .await
+                .map_err(|e| format!("Backend did not switch to {}: {}", language, e))?;
+            let switched = events
+                .iter()
+                .any(|event| matches!(event, ShellEvent::LanguageChanged { language: changed } if changed == language));
+            if !switched {
+                return Err(format!("Backend did not switch to {}", language));
+            }
+        }
+
+        self.set_language(language).await;
+        Ok(())
+    }
+
+    /// Set the interpreter and model used from the next start on
+    pub async fn configure(&self, python: Option<PathBuf>, model: Option<String>) {
+        let mut state = self.inner.state.lock().await;
+        state.config.python = python;
+        state.config.model = model;
+    }
+}
+
+/// Crash restart policy for the supervised backend
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// Delay before the first restart, doubled on each further crash
+    pub initial_delay: Duration,
+    /// Upper bound for the restart delay
+    pub max_delay: Duration,
+    /// Crashes tolerated within `window` before giving up
+    pub max_crashes: u32,
+    /// Sliding window in which crashes are counted
+    pub window: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            max_crashes: 5,
+            window: Duration::from_secs(120),
         }
     }
 }
 
+impl RestartPolicy {
+    /// Delay before restart number `attempt` (starting at 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+/// Lifecycle events pushed to the webview
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum BackendEvent {
+    /// The backend process was spawned
+    Starting { port: u16, pid: u32 },
+    /// The backend answers on its HTTP port
+    Ready { port: u16, startup_ms: u64 },
+    /// The backend did not come up
+    Failed { error: StartupError },
+    /// The backend is running but not answering
+    Unhealthy { reason: String },
+    /// The backend answers again after being unhealthy
+    Recovered,
+    /// The backend process exited; `expected` is true for a requested stop
+    Exited { code: Option<i32>, signal: Option<i32>, expected: bool },
+    /// The backend crashed and will be restarted after `delay_ms`
+    Restarting { attempt: u32, delay_ms: u64 },
+    /// The backend crashed too often and will not be restarted
+    GaveUp { crashes: u32, window_secs: u64 },
+}
+
+impl BackendEvent {
+    /// Tauri event name for this event
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendEvent::Starting { .. } => "backend-starting",
+            BackendEvent::Ready { .. } => "backend-ready",
+            BackendEvent::Failed { .. } => "backend-failed",
+            BackendEvent::Unhealthy { .. } => "backend-unhealthy",
+            BackendEvent::Recovered => "backend-recovered",
+            BackendEvent::Exited { .. } => "backend-exited",
+            BackendEvent::Restarting { .. } => "backend-restarting",
+            BackendEvent::GaveUp { .. } => "backend-gave-up",
+        }
+    }
+
+    fn exited(status: &ExitStatus, expected: bool) -> Self {
+        let (code, signal) = process::exit_details(status);
+        BackendEvent::Exited { code, signal, expected }
+    }
+}
+
+/// Backend status as reported to the UI
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ServerStatus {
+    pub running: bool,
+    pub ws_port: Option<u16>,
+    pub http_port: Option<u16>,
+    pub ws_url: Option<String>,
+    pub http_url: Option<String>,
+    pub health: Health,
+}
+
+/// Spawn the backend described by `config` with log forwarding.
+///
+/// Must run on a runtime worker thread: `process::tie_to_parent` has the
+/// kernel SIGTERM the backend when the spawning thread exits, and workers
+/// live as long as the app, unlike `spawn_blocking` threads.
+fn spawn_backend(
+    config: &LaunchConfig,
+    python: &Path,
+    logs: &Arc<LogBuffer>,
+) -> Result<(Child, Arc<StderrTail>), StartupError> {
+    let args = config.args();
+
+    log::info!("Starting Python server with: {} {}", python.display(), args.join(" "));
+
+    let mut child = process::isolate(process::tie_to_parent(&mut Command::new(python)))
+        .args(&args)
+        .env(TOKEN_ENV, &config.token)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| StartupError::Spawn { message: e.to_string() })?;
+
+    log::info!("Python server started with PID: {:?}", child.id());
+
+    // Start log forwarding tasks
+    let stderr = Arc::new(StderrTail::default());
+    start_log_forwarder(&mut child, stderr.clone(), logs.clone());
+
+    Ok((child, stderr))
+}
+
+/// Forward classified Python process logs into `logs` and `backend.log`,
+/// keeping a stderr tail
+fn start_log_forwarder(child: &mut Child, tail: Arc<StderrTail>, logs: Arc<LogBuffer>) {
+    // Forward stdout
+    if let Some(stdout) = child.stdout.take() {
+        let logs = logs.clone();
+        tokio::spawn(async move {
+            let mut classifier = Classifier::new(Level::Info);
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(record) = classifier.feed(&line) {
+                    forward_record(&logs, Stream::Stdout, record);
+                }
+            }
+            if let Some(record) = classifier.finish() {
+                forward_record(&logs, Stream::Stdout, record);
+            }
+        });
+    }
+
+    // Forward stderr; unrecognised lines stay warnings
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(async move {
+            let mut classifier = Classifier::new(Level::Warning);
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tail.push(&line);
+                if let Some(record) = classifier.feed(&line) {
+                    forward_record(&logs, Stream::Stderr, record);
+                }
+            }
+            if let Some(record) = classifier.finish() {
+                forward_record(&logs, Stream::Stderr, record);
+            }
+            tail.closed.send_replace(true);
+        });
+    }
+}
+
+fn forward_record(logs: &LogBuffer, stream: Stream, record: Record) {
+    log::log!(target: BACKEND_TARGET, record.level.into(), "{}", record.message);
+    logs.push(stream, record);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
+    #[tokio::test]
+    async fn test_managers_are_independent() {
+        let first = ServerManager::new("en");
+        let second = ServerManager::new("pl");
+
+        first.set_language("de").await;
+
+        assert_eq!(first.language().await, "de");
+        assert_eq!(second.language().await, "pl");
+        assert_ne!(first.ui_url().await, second.ui_url().await);
+    }
+
+    #[tokio::test]
+    async fn test_status_without_backend() {
+        let manager = ServerManager::default();
+
+        // Stopping an idle manager is a no-op
+        assert_eq!(manager.stop().await, ShutdownResult::not_running());
+
+        let status = manager.status().await;
+        assert!(!status.running);
+        assert_eq!(status.ws_url, None);
+        assert_eq!(status.http_url, None);
+        assert_eq!(status.health, Health::Stopped);
+        assert_eq!(manager.wait_ready().await, Err(StartupError::Stopped));
+    }
+
+    #[tokio::test]
+    async fn test_giving_up_detaches_probes() {
+        let manager = ServerManager::default();
+        let launch = manager.inner.state.lock().await.launch;
+
+        manager.give_up(0, 6).await;
+
+        assert_ne!(manager.inner.state.lock().await.launch, launch);
+        assert_eq!(manager.wait_ready().await, Err(StartupError::CrashLoop { crashes: 6 }));
+    }
+
+    #[test]
+    fn test_backend_event_payload() {
+        let event = BackendEvent::Ready { port: 8765, startup_ms: 1200 };
+
+        assert_eq!(event.name(), "backend-ready");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"state": "ready", "port": 8765, "startup_ms": 1200})
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exited_event_reports_signal() {
+        let status = std::process::Command::new("sh")
+            .args(["-c", "kill -TERM $$"])
+            .status()
+            .unwrap();
+
+        assert_eq!(
+            BackendEvent::exited(&status, false),
+            BackendEvent::Exited { code: None, signal: Some(libc::SIGTERM), expected: false }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stderr_tail_keeps_last_lines() {
+        let tail = StderrTail::default();
+        for i in 0..STDERR_TAIL_LINES + 5 {
+            tail.push(&format!("line {}", i));
+        }
+        tail.closed.send_replace(true);
+
+        let lines = tail.drain().await;
+        assert_eq!(lines.len(), STDERR_TAIL_LINES);
+        assert_eq!(lines[0], "line 5");
+    }
+
+    /// Set by `test_backend_dies_with_launcher` to the helper role a
+    /// re-run of this test binary plays
+    const ROLE_ENV: &str = "VOICE_SHELL_TEST_ROLE";
+
+    /// Re-run this test binary as one of the ignored helpers below
+    #[cfg(target_os = "linux")]
+    fn run_helper(name: &str, role: &str) -> std::process::Command {
+        let mut command = std::process::Command::new(std::env::current_exe().unwrap());
+        command
+            .args(["--ignored", "--exact", &format!("server::tests::{}", name), "--nocapture", "--test-threads=1"])
+            .env(ROLE_ENV, role);
+        command
+    }
+
+    /// Stand-in for the app: spawns a backend like `ServerManager` does,
+    /// from a task on a worker thread, with a script in place of Python
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[ignore = "run by test_backend_dies_with_launcher"]
+    async fn launcher_helper() {
+        use std::os::unix::fs::PermissionsExt;
+
+        if std::env::var(ROLE_ENV).as_deref() != Ok("launcher") {
+            return;
+        }
+        let python = std::env::temp_dir().join(format!("voice-shell-launcher-{}", std::process::id()));
+        std::fs::write(&python, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        std::fs::set_permissions(&python, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let backend = tokio::spawn(async move {
+            let (child, _) = spawn_backend(&LaunchConfig::new("en"), &python, &Arc::default()).unwrap();
+            child
+        })
+        .await
+        .unwrap();
+        println!("BACKEND_PID {}", backend.id().unwrap());
+        tokio::time::sleep(Duration::from_secs(30)).await;
+    }
+
+    /// Kills a launcher and checks that its backend went down with it.
+    ///
+    /// Runs in its own process, since it becomes a subreaper to reap the
+    /// orphaned backend.
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "run by test_backend_dies_with_launcher"]
+    fn reaper_helper() {
+        use std::io::{BufRead, BufReader};
+
+        if std::env::var(ROLE_ENV).as_deref() != Ok("reaper") {
+            return;
+        }
+        // Orphans are re-parented to us, so we can reap the backend
+        unsafe {
+            libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1);
+        }
+
+        let mut launcher = run_helper("launcher_helper", "launcher").stdout(Stdio::piped()).spawn().unwrap();
+
+        let backend: libc::pid_t = BufReader::new(launcher.stdout.take().unwrap())
+            .lines()
+            .map_while(Result::ok)
+            // libtest may print the test name on the same line
+            .find_map(|line| line.split_once("BACKEND_PID ").map(|(_, pid)| pid.trim().parse().unwrap()))
+            .expect("launcher did not report its backend");
+
+        // Still running while the launcher is; the spawning task has ended
+        std::thread::sleep(Duration::from_millis(500));
+        assert_eq!(unsafe { libc::kill(backend, 0) }, 0, "backend died before its launcher");
+
+        launcher.kill().unwrap();
+        launcher.wait().unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut status = 0;
+        loop {
+            let reaped = unsafe { libc::waitpid(backend, &mut status, libc::WNOHANG) };
+            if reaped == backend {
+                break;
+            }
+            assert!(Instant::now() < deadline, "backend outlived its launcher");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        assert!(libc::WIFSIGNALED(status));
+        assert_eq!(libc::WTERMSIG(status), libc::SIGTERM);
+    }
+
+    #[cfg(target_os = "linux")]
     #[test]
-    fn test_find_python() {
-        assert!(find_python().is_some());
+    fn test_backend_dies_with_launcher() {
+        let output = run_helper("reaper_helper", "reaper").output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(output.status.success(), "{}{}", stdout, String::from_utf8_lossy(&output.stderr));
+        // Guard against the helper being filtered out and passing vacuously
+        assert!(stdout.contains("1 passed"), "{}", stdout);
+    }
+
+    #[test]
+    fn test_restart_backoff_doubles_and_caps() {
+        let policy = RestartPolicy {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(3),
+            ..RestartPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(3));
+        assert_eq!(policy.backoff(40), Duration::from_secs(3));
     }
 }
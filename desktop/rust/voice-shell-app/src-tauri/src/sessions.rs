@@ -0,0 +1,167 @@
+// Session Management
+// Lists, creates, switches, renames and closes backend conversation sessions
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::client::ShellClient;
+use crate::protocol::{ClientMessage, Session, ShellEvent};
+
+/// How long the backend may take to answer a session request
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Every session and which one is current
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionList {
+    pub sessions: Vec<Session>,
+    pub current: Option<String>,
+}
+
+impl SessionList {
+    /// Follow an event that carries the session list; returns whether
+    /// anything changed
+    pub fn apply(&mut self, event: &ShellEvent) -> bool {
+        let (sessions, current) = match event {
+            // Also re-sent as a plain list refresh, e.g. when a command ends;
+            // only a session not seen before has become current
+            ShellEvent::SessionCreated { session, sessions } => {
+                let created = !self.sessions.iter().any(|known| known.id == session.id);
+                (sessions, if created { Some(session.id.clone()) } else { self.current.clone() })
+            }
+            ShellEvent::SessionSwitched { session, sessions, .. } => (sessions, Some(session.id.clone())),
+            ShellEvent::SessionRenamed { sessions, .. } => (sessions, self.current.clone()),
+            // The backend falls back to the newest session
+            ShellEvent::SessionClosed { session_id, sessions } => {
+                let current = match self.current {
+                    Some(ref current) if current != session_id => Some(current.clone()),
+                    _ => sessions.last().map(|session| session.id.clone()),
+                };
+                (sessions, current)
+            }
+            ShellEvent::SessionsList { sessions, current, .. } => (sessions, current.clone()),
+            _ => return false,
+        };
+
+        let list = SessionList { sessions: sessions.clone(), current };
+        if *self == list {
+            return false;
+        }
+        *self = list;
+        true
+    }
+
+    /// The current session
+    pub fn current(&self) -> Option<&Session> {
+        let current = self.current.as_deref()?;
+        self.sessions.iter().find(|session| session.id == current)
+    }
+}
+
+/// Every session; the backend creates the first one if there are none
+pub async fn list(client: &ShellClient) -> Result<SessionList, String> {
+    request(client, &ClientMessage::GetSessions, |event| {
+        let mut list = SessionList::default();
+        list.apply(&event).then_some(list)
+    })
+    .await?
+    .ok_or_else(|| "Backend did not list its sessions".to_string())
+}
+
+/// Start a session, named after its number unless `name` is given, and
+/// make it current
+pub async fn create(client: &ShellClient, name: Option<String>) -> Result<Session, String> {
+    request(client, &ClientMessage::NewSession(name), |event| match event {
+        ShellEvent::SessionCreated { session, .. } => Some(session),
+        _ => None,
+    })
+    .await?
+    .ok_or_else(|| "Backend did not create a session".to_string())
+}
+
+/// Make session `id` current
+pub async fn switch(client: &ShellClient, id: &str) -> Result<Session, String> {
+    request(client, &ClientMessage::SwitchSession(id.to_string()), |event| match event {
+        ShellEvent::SessionSwitched { session, .. } if session.id == id => Some(session),
+        _ => None,
+    })
+    .await?
+    .ok_or_else(|| format!("No session {}", id))
+}
+
+/// Give session `id` a new name
+pub async fn rename(client: &ShellClient, id: &str, name: &str) -> Result<Session, String> {
+    if name.trim().is_empty() {
+        return Err("Session name must not be empty".to_string());
+    }
+    let message = ClientMessage::RenameSession { id: id.to_string(), name: name.to_string() };
+    request(client, &message, |event| match event {
+        ShellEvent::SessionRenamed { session, .. } if session.id == id => Some(session),
+        _ => None,
+    })
+    .await?
+    .ok_or_else(|| format!("No session {}", id))
+}
+
+/// Stop and delete session `id`; returns the remaining sessions
+pub async fn close(client: &ShellClient, id: &str) -> Result<Vec<Session>, String> {
+    request(client, &ClientMessage::CloseSession(id.to_string()), |event| match event {
+        ShellEvent::SessionClosed { session_id, sessions } if session_id == id => Some(sessions),
+        _ => None,
+    })
+    .await?
+    .ok_or_else(|| format!("Backend did not close session {}", id))
+}
+
+/// Send `message` and pick the reply out of the events it caused
+async fn request<T>(
+    client: &ShellClient,
+    message: &ClientMessage,
+    reply: impl FnMut(ShellEvent) -> Option<T>,
+) -> Result<Option<T>, String> {
+    let (events, _) = client.exchange(message, REPLY_TIMEOUT).await?;
+    Ok(events.into_iter().find_map(reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::SessionStatus;
+
+    fn session(id: &str, name: &str) -> Session {
+        Session {
+            id: id.to_string(),
+            name: name.to_string(),
+            command: String::new(),
+            status: SessionStatus::Idle,
+            output_lines: 0,
+            created_at: "2026-10-15T22:44:55".to_string(),
+            has_process: false,
+        }
+    }
+
+    #[test]
+    fn test_follows_session_events() {
+        let mut list = SessionList::default();
+        let (s1, s2) = (session("s1", "Session 1"), session("s2", "Session 2"));
+
+        let created = ShellEvent::SessionCreated { session: s2.clone(), sessions: vec![s1.clone(), s2.clone()] };
+        assert!(list.apply(&created));
+        assert_eq!(list.current().map(|s| s.name.as_str()), Some("Session 2"));
+        assert!(!list.apply(&created));
+
+        // A command ending in s1 refreshes the list without switching to it
+        let running = Session { status: SessionStatus::Running, ..s1.clone() };
+        assert!(list.apply(&ShellEvent::SessionCreated { session: running.clone(), sessions: vec![running, s2.clone()] }));
+        assert_eq!(list.current.as_deref(), Some("s2"));
+
+        let renamed = session("s1", "Front door");
+        assert!(list.apply(&ShellEvent::SessionRenamed { session: renamed.clone(), sessions: vec![renamed, s2.clone()] }));
+        assert_eq!((list.sessions[0].name.as_str(), list.current.as_deref()), ("Front door", Some("s2")));
+
+        assert!(list.apply(&ShellEvent::SessionClosed { session_id: "s2".to_string(), sessions: vec![s1] }));
+        assert_eq!(list.current.as_deref(), Some("s1"));
+
+        assert!(!list.apply(&ShellEvent::TtsSpeak { text: "New conversation".to_string() }));
+    }
+}
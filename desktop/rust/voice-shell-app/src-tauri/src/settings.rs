@@ -0,0 +1,365 @@
+// Desktop Settings
+// Persistent launcher settings in the user's config directory
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::logfile;
+use crate::ports::PortRequest;
+use crate::shutdown::ClosePolicy;
+
+/// Directory under the platform config and data dirs; matches the bundle
+/// identifier
+pub const APP_DIR: &str = "com.streamware.voice-shell";
+
+/// Settings file name
+const FILE_NAME: &str = "settings.json";
+
+/// Languages with a translation pack in `streamware/i18n/translations.py`
+pub const LANGUAGES: [(&str, &str); 3] = [("en", "English"), ("pl", "Polski"), ("de", "Deutsch")];
+
+/// Reject languages without a translation pack
+pub fn check_language(language: &str) -> Result<(), String> {
+    if LANGUAGES.iter().any(|(code, _)| *code == language) {
+        Ok(())
+    } else {
+        Err(format!("Unsupported language: {}", language))
+    }
+}
+
+/// Everything the user can configure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// WebSocket port; a free port when `None`
+    pub ws_port: Option<u16>,
+    /// HTTP UI port; a free port when `None`
+    pub http_port: Option<u16>,
+    /// Conversation language
+    pub language: String,
+    /// LLM model; the backend default when `None`
+    pub model: Option<String>,
+    /// Python interpreter; searched for when `None`
+    pub python_path: Option<PathBuf>,
+    /// What closing the main window does
+    pub close_policy: ClosePolicy,
+    /// Lowest level written to the log files, e.g. "info" or "debug"
+    pub log_level: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ws_port: None,
+            http_port: None,
+            language: crate::server::DEFAULT_LANGUAGE.to_string(),
+            model: None,
+            python_path: None,
+            close_policy: ClosePolicy::default(),
+            log_level: "info".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// Ports to request from `ports::allocate`
+    pub fn ports(&self) -> PortRequest {
+        PortRequest { ws: self.ws_port, http: self.http_port }
+    }
+
+    /// Check that the settings can be used to launch the backend
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some((_, e)) = self.invalid_fields().into_iter().next() {
+            return Err(e);
+        }
+        if let Some(ref python) = self.python_path {
+            if !python.is_file() {
+                return Err(format!("Python interpreter not found: {}", python.display()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fields that can never be used, with the reason.
+    ///
+    /// A missing `python_path` is not one of them: the interpreter may come
+    /// back, and `python::Resolver` reports it when launching.
+    fn invalid_fields(&self) -> Vec<(&'static str, String)> {
+        let mut invalid = Vec::new();
+        let out_of_range = "Ports must be between 1 and 65535; leave empty for a free port";
+        if self.ws_port == Some(0) {
+            invalid.push(("ws_port", out_of_range.to_string()));
+        }
+        if self.http_port == Some(0) {
+            invalid.push(("http_port", out_of_range.to_string()));
+        } else if self.ws_port.is_some() && self.ws_port == self.http_port {
+            invalid.push(("http_port", "WebSocket and HTTP ports must differ".to_string()));
+        }
+        if let Err(e) = check_language(&self.language) {
+            invalid.push(("language", e));
+        }
+        if self.model.as_deref().is_some_and(|model| model.trim().is_empty()) {
+            invalid.push(("model", "Model name must not be empty".to_string()));
+        }
+        if let Err(e) = logfile::parse_level(&self.log_level) {
+            invalid.push(("log_level", e));
+        }
+        invalid
+    }
+
+    /// Put a field named by `invalid_fields` back to its default
+    fn reset(&mut self, field: &str) {
+        let defaults = Settings::default();
+        match field {
+            "ws_port" => self.ws_port = defaults.ws_port,
+            "http_port" => self.http_port = defaults.http_port,
+            "language" => self.language = defaults.language,
+            "model" => self.model = defaults.model,
+            "log_level" => self.log_level = defaults.log_level,
+            _ => {}
+        }
+    }
+
+    /// Names of the fields that differ from `other`
+    pub fn changed_fields(&self, other: &Settings) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.ws_port != other.ws_port {
+            changed.push("ws_port");
+        }
+        if self.http_port != other.http_port {
+            changed.push("http_port");
+        }
+        if self.language != other.language {
+            changed.push("language");
+        }
+        if self.model != other.model {
+            changed.push("model");
+        }
+        if self.python_path != other.python_path {
+            changed.push("python_path");
+        }
+        if self.close_policy != other.close_policy {
+            changed.push("close_policy");
+        }
+        if self.log_level != other.log_level {
+            changed.push("log_level");
+        }
+        changed
+    }
+}
+
+/// Result of `update_settings`, also sent as the `settings-changed` event
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SettingsChange {
+    pub settings: Settings,
+    pub changed: Vec<&'static str>,
+    /// The running backend keeps its old ports, model and interpreter
+    pub restart_required: bool,
+}
+
+/// Settings loaded from and saved to one JSON file
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    /// Default location in the platform config directory
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(APP_DIR)
+            .join(FILE_NAME)
+    }
+
+    /// Load settings from `path`, falling back to defaults for a missing or
+    /// unreadable file and for each unusable field
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let settings = match read(&path) {
+            Ok(Some(settings)) => settings,
+            Ok(None) => Settings::default(),
+            Err(e) => {
+                log::warn!("Ignoring settings in {}: {}", path.display(), e);
+                Settings::default()
+            }
+        };
+        Self { path, settings: Mutex::new(settings) }
+    }
+
+    /// Current settings
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Settings as `update` would save them, without saving anything
+    pub fn preview(&self, patch: &serde_json::Value) -> Result<Settings, String> {
+        merge(&self.settings.lock().unwrap(), patch)
+    }
+
+    /// Merge a partial JSON object into the settings, validate and save them
+    pub fn update(&self, patch: &serde_json::Value) -> Result<SettingsChange, String> {
+        let mut settings = self.settings.lock().unwrap();
+        let updated = merge(&settings, patch)?;
+
+        let changed = updated.changed_fields(&settings);
+        if !changed.is_empty() {
+            write(&self.path, &updated)?;
+            *settings = updated.clone();
+        }
+
+        Ok(SettingsChange {
+            restart_required: changed
+                .iter()
+                .any(|field| matches!(*field, "ws_port" | "http_port" | "model" | "python_path")),
+            settings: updated,
+            changed,
+        })
+    }
+}
+
+/// Apply a partial JSON object to `settings` and validate the result
+fn merge(settings: &Settings, patch: &serde_json::Value) -> Result<Settings, String> {
+    let patch = patch.as_object().ok_or("Settings update must be an object")?;
+
+    let mut merged = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    for (key, value) in patch {
+        if merged.get(key).is_none() {
+            return Err(format!("Unknown setting: {}", key));
+        }
+        merged[key] = value.clone();
+    }
+    let updated: Settings = serde_json::from_value(merged).map_err(|e| format!("Invalid settings: {}", e))?;
+    updated.validate()?;
+    Ok(updated)
+}
+
+fn read(path: &Path) -> Result<Option<Settings>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let stored: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&text).map_err(|e| e.to_string())?;
+
+    // Take the stored fields one by one so a bad one does not cost the rest
+    let mut merged = serde_json::to_value(Settings::default()).map_err(|e| e.to_string())?;
+    for (key, value) in stored {
+        if merged.get(&key).is_none() {
+            log::warn!("Ignoring unknown setting {} in {}", key, path.display());
+            continue;
+        }
+        let mut candidate = merged.clone();
+        candidate[&key] = value;
+        match serde_json::from_value::<Settings>(candidate.clone()) {
+            Ok(_) => merged = candidate,
+            Err(e) => log::warn!("Resetting setting {} in {}: {}", key, path.display(), e),
+        }
+    }
+
+    let mut settings: Settings = serde_json::from_value(merged).map_err(|e| e.to_string())?;
+    for (field, reason) in settings.invalid_fields() {
+        log::warn!("Resetting setting {} in {}: {}", field, path.display(), reason);
+        settings.reset(field);
+    }
+    Ok(Some(settings))
+}
+
+/// Write through a temporary file so a crash never leaves half a file
+fn write(path: &Path, settings: &Settings) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, text)
+        .and_then(|_| fs::rename(&temp, path))
+        .map_err(|e| format!("Failed to save {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("voice-shell-settings-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join(FILE_NAME)
+    }
+
+    #[test]
+    fn test_missing_file_gives_defaults() {
+        let store = SettingsStore::load(temp_path("missing"));
+        assert_eq!(store.get(), Settings::default());
+    }
+
+    #[test]
+    fn test_update_persists_and_reports_changes() {
+        let path = temp_path("update");
+        let store = SettingsStore::load(&path);
+
+        let change = store.update(&json!({ "language": "pl", "ws_port": 9100 })).unwrap();
+        assert_eq!(change.changed, ["ws_port", "language"]);
+        assert!(change.restart_required);
+
+        let reloaded = SettingsStore::load(&path).get();
+        assert_eq!(reloaded.language, "pl");
+        assert_eq!(reloaded.ports(), PortRequest { ws: Some(9100), http: None });
+    }
+
+    #[test]
+    fn test_update_rejects_invalid_settings() {
+        let store = SettingsStore::load(temp_path("invalid"));
+
+        assert!(store.update(&json!({ "language": "xx" })).is_err());
+        assert!(store.update(&json!({ "ws_port": 9000, "http_port": 9000 })).is_err());
+        assert!(store.update(&json!({ "python_path": "/nonexistent/python" })).is_err());
+        assert!(store.update(&json!({ "colour": "blue" })).is_err());
+        assert!(store.update(&json!({ "log_level": "loud" })).is_err());
+        assert_eq!(store.get(), Settings::default());
+    }
+
+    #[test]
+    fn test_preview_saves_nothing() {
+        let path = temp_path("preview");
+        let store = SettingsStore::load(&path);
+
+        assert!(store.preview(&json!({ "language": "pl", "ws_port": 0 })).is_err());
+        assert_eq!(store.preview(&json!({ "language": "pl" })).unwrap().language, "pl");
+        assert_eq!(store.get(), Settings::default());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_bad_field_keeps_other_settings() {
+        let path = temp_path("partial");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let stored = json!({
+            "language": "pl",
+            "ws_port": 9100,
+            "http_port": "eighty",
+            "log_level": "loud",
+            "python_path": "/moved/python3",
+            "close_policy": "minimize_to_tray",
+        });
+        fs::write(&path, stored.to_string()).unwrap();
+
+        let settings = SettingsStore::load(&path).get();
+        assert_eq!((settings.language.as_str(), settings.ws_port), ("pl", Some(9100)));
+        assert_eq!((settings.http_port, settings.log_level.as_str()), (None, "info"));
+        assert_eq!(settings.python_path, Some(PathBuf::from("/moved/python3")));
+        assert_eq!(settings.close_policy, ClosePolicy::MinimizeToTray);
+    }
+
+    #[test]
+    fn test_corrupt_file_falls_back_to_defaults() {
+        let path = temp_path("corrupt");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "{ not json").unwrap();
+
+        assert_eq!(SettingsStore::load(&path).get(), Settings::default());
+    }
+}
@@ -0,0 +1,87 @@
+// App Shutdown
+// Window close policy and termination signals of the Tauri process
+
+use std::fmt;
+
+/// What closing the main window does
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClosePolicy {
+    /// Quit the app and stop the backend
+    #[default]
+    Quit,
+    /// Hide the window and keep the backend running in the tray
+    MinimizeToTray,
+}
+
+impl fmt::Display for ClosePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClosePolicy::Quit => write!(f, "quit"),
+            ClosePolicy::MinimizeToTray => write!(f, "minimize_to_tray"),
+        }
+    }
+}
+
+/// Resolves with the signal name once the process is asked to terminate
+pub async fn termination_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let (Ok(mut interrupt), Ok(mut terminate), Ok(mut hangup)) = (
+            signal(SignalKind::interrupt()),
+            signal(SignalKind::terminate()),
+            signal(SignalKind::hangup()),
+        ) else {
+            log::error!("Failed to install signal handlers");
+            return std::future::pending().await;
+        };
+
+        tokio::select! {
+            _ = interrupt.recv() => "SIGINT",
+            _ = terminate.recv() => "SIGTERM",
+            _ = hangup.recv() => "SIGHUP",
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => "Ctrl+C",
+            Err(e) => {
+                log::error!("Failed to install Ctrl+C handler: {}", e);
+                std::future::pending().await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_policy_round_trip() {
+        for policy in [ClosePolicy::Quit, ClosePolicy::MinimizeToTray] {
+            let value = serde_json::to_value(policy).unwrap();
+            assert_eq!(value, serde_json::Value::String(policy.to_string()));
+            assert_eq!(serde_json::from_value::<ClosePolicy>(value).unwrap(), policy);
+        }
+        assert!(serde_json::from_str::<ClosePolicy>(r#""hide""#).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_termination_signal() {
+        let received = tokio::spawn(termination_signal());
+        // Give the task time to install its handlers
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        unsafe {
+            libc::kill(libc::getpid(), libc::SIGHUP);
+        }
+
+        assert_eq!(received.await.unwrap(), "SIGHUP");
+    }
+}
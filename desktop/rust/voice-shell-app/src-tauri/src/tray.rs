@@ -0,0 +1,312 @@
+// System Tray
+// Tray icon and menu with backend controls
+
+use std::sync::Mutex;
+
+use serde_json::json;
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, Wry};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::indicator::{self, Indicator};
+use crate::server::ServerManager;
+use crate::sessions::{self, SessionList};
+use crate::settings::{Settings, SettingsStore, LANGUAGES};
+
+/// Id of the one tray icon
+const TRAY_ID: &str = "main";
+
+/// Menu items that change at runtime
+struct TrayMenu {
+    status: MenuItem<Wry>,
+    languages: Vec<(&'static str, CheckMenuItem<Wry>)>,
+    indicator: Mutex<Indicator>,
+    /// One check item per backend session
+    sessions: Submenu<Wry>,
+    session_list: Mutex<SessionList>,
+}
+
+/// Build the tray icon and keep it in sync with the backend
+pub fn create(app: &AppHandle, language: &str) -> tauri::Result<()> {
+    let indicator = Indicator::default();
+    let status = MenuItem::with_id(app, "status", indicator.status_text(), false, None::<&str>)?;
+    let toggle = MenuItem::with_id(app, "toggle", "Show/Hide Window", true, None::<&str>)?;
+    let new_session = MenuItem::with_id(app, "new_session", "New Session", true, Some("CmdOrCtrl+T"))?;
+    let restart = MenuItem::with_id(app, "restart", "Restart Backend", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, Some("CmdOrCtrl+Q"))?;
+
+    let languages = LANGUAGES
+        .iter()
+        .map(|&(code, name)| {
+            CheckMenuItem::with_id(app, format!("language:{}", code), name, true, code == language, None::<&str>)
+                .map(|item| (code, item))
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let language_items: Vec<&dyn IsMenuItem<Wry>> =
+        languages.iter().map(|(_, item)| item as &dyn IsMenuItem<Wry>).collect();
+    let language_menu = Submenu::with_items(app, "Language", true, &language_items)?;
+    let sessions = Submenu::with_items(app, "Sessions", true, &[])?;
+    fill_sessions_menu(app, &sessions, &SessionList::default())?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &toggle,
+            &new_session,
+            &sessions,
+            &restart,
+            &language_menu,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Streamware Voice Shell")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                toggle_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+
+    app.manage(TrayMenu {
+        status,
+        languages,
+        indicator: Mutex::new(indicator),
+        sessions,
+        session_list: Mutex::new(SessionList::default()),
+    });
+    follow_backend(app.clone());
+
+    Ok(())
+}
+
+/// Show the main window if hidden, hide it otherwise
+pub fn toggle_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    let server = app.state::<ServerManager>().inner().clone();
+
+    match id {
+        "toggle" => toggle_window(app),
+        "new_session" => {
+            tauri::async_runtime::spawn(async move {
+                let created = match server.connected_client().await {
+                    Ok(client) => sessions::create(client, None).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = created {
+                    log::warn!("Failed to create session: {}", e);
+                }
+            });
+        }
+        "restart" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let language = server.language().await;
+                if let Err(e) = crate::restart_backend(&app, None, &language).await {
+                    log::error!("Failed to restart server: {}", e);
+                }
+            });
+        }
+        "quit" => app.exit(0),
+        _ => {
+            if let Some(code) = id.strip_prefix("language:") {
+                switch_language(app, code.to_string());
+            } else if let Some(session) = id.strip_prefix("session:") {
+                switch_session(app, session.to_string());
+            }
+        }
+    }
+}
+
+fn switch_language(app: &AppHandle, language: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let server = app.state::<ServerManager>().inner().clone();
+        let store = app.state::<SettingsStore>();
+
+        let result = match server.change_language(&language).await {
+            Ok(()) => store.update(&json!({ "language": language })),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(change) => crate::settings_changed(&app, &change),
+            Err(e) => {
+                log::warn!("Failed to switch language: {}", e);
+                // Put the check mark back on the language still in use
+                settings_changed(&app, &store.get());
+            }
+        }
+    });
+}
+
+fn switch_session(app: &AppHandle, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let server = app.state::<ServerManager>().inner().clone();
+        let switched = match server.connected_client().await {
+            Ok(client) => sessions::switch(client, &id).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = switched {
+            log::warn!("Failed to switch session: {}", e);
+            // Put the check mark back on the current session
+            update_sessions(&app, |_| true);
+        }
+    });
+}
+
+/// Reflect saved settings in the menu
+pub fn settings_changed(app: &AppHandle, settings: &Settings) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        for (code, item) in &menu.languages {
+            let _ = item.set_checked(*code == settings.language);
+        }
+    }
+}
+
+/// Update the tray from backend lifecycle and command events
+fn follow_backend(app: AppHandle) {
+    let server = app.state::<ServerManager>().inner().clone();
+    let mut events = server.subscribe();
+    let mut shell_events = server.client().subscribe();
+
+    let lifecycle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => update(&lifecycle, |indicator| {
+                    indicator.apply(&event);
+                    true
+                }),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // Watch for running commands; replayed history says nothing about now,
+    // but session events carry the whole list and may be applied in order
+    let shell = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match shell_events.recv().await {
+                Ok(event) => {
+                    if let Some(known) = event.known() {
+                        update_sessions(&shell, |list| list.apply(known));
+                        if !event.replayed {
+                            update(&shell, |indicator| indicator.apply_shell_event(known));
+                        }
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // Ask for the sessions on every (re)connect; the reply arrives above
+    let mut connection = server.client().connection();
+    tauri::async_runtime::spawn(async move {
+        while connection.wait_for(|connected| *connected).await.is_ok() {
+            if let Err(e) = sessions::list(server.client()).await {
+                log::warn!("Failed to list sessions: {}", e);
+            }
+            if connection.wait_for(|connected| !*connected).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Change the session list and rebuild its submenu if `change` reports a change
+fn update_sessions(app: &AppHandle, change: impl FnOnce(&mut SessionList) -> bool) {
+    let Some(menu) = app.try_state::<TrayMenu>() else {
+        return;
+    };
+    let list = {
+        let mut list = menu.session_list.lock().unwrap();
+        if !change(&mut list) {
+            return;
+        }
+        list.clone()
+    };
+    if let Err(e) = fill_sessions_menu(app, &menu.sessions, &list) {
+        log::warn!("Failed to update sessions menu: {}", e);
+    }
+}
+
+/// Replace the items of the Sessions submenu, checking the current session
+fn fill_sessions_menu(app: &AppHandle, submenu: &Submenu<Wry>, list: &SessionList) -> tauri::Result<()> {
+    for item in submenu.items()? {
+        submenu.remove(&item)?;
+    }
+    if list.sessions.is_empty() {
+        return submenu.append(&MenuItem::with_id(app, "sessions:none", "No Sessions", false, None::<&str>)?);
+    }
+    let current = list.current().map(|session| session.id.as_str());
+    for session in &list.sessions {
+        let checked = current == Some(session.id.as_str());
+        let item = CheckMenuItem::with_id(app, format!("session:{}", session.id), &session.name, true, checked, None::<&str>)?;
+        submenu.append(&item)?;
+    }
+    Ok(())
+}
+
+/// Change the indicator and redraw the tray if `change` reports a change
+fn update(app: &AppHandle, change: impl FnOnce(&mut Indicator) -> bool) {
+    let Some(menu) = app.try_state::<TrayMenu>() else {
+        return;
+    };
+    let indicator = {
+        let mut indicator = menu.indicator.lock().unwrap();
+        if !change(&mut indicator) {
+            return;
+        }
+        indicator.clone()
+    };
+
+    let _ = menu.status.set_text(indicator.status_text());
+
+    let (Some(tray), Some(icon)) = (app.tray_by_id(TRAY_ID), app.default_window_icon()) else {
+        return;
+    };
+    let icon = match indicator.badge() {
+        Some(badge) => Image::new_owned(
+            indicator::draw_badge(icon.rgba(), icon.width(), icon.height(), badge.color()),
+            icon.width(),
+            icon.height(),
+        ),
+        None => icon.clone(),
+    };
+    let _ = tray.set_icon(Some(icon));
+    let _ = tray.set_tooltip(Some(format!("Streamware Voice Shell - {}", indicator.status_text())));
+}
@@ -200,6 +200,14 @@ class VoiceShellDB:
             """, (now, status, session_id))
             conn.commit()
     
+    def rename_session(self, session_id: str, name: str) -> None:
+        """Change a session's name."""
+        with sqlite3.connect(self.db_path) as conn:
+            conn.execute(
+                "UPDATE sessions SET name = ? WHERE id = ?", (name, session_id)
+            )
+            conn.commit()
+    
     def get_session(self, session_id: str) -> Optional[SessionRecord]:
         """Get a session by ID."""
         with sqlite3.connect(self.db_path) as conn:
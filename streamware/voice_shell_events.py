@@ -28,6 +28,7 @@ class EventType(str, Enum):
     SESSION_CREATED = "session_created"
     SESSION_SWITCHED = "session_switched"
     SESSION_CLOSED = "session_closed"
+    SESSION_RENAMED = "session_renamed"
     SESSION_OUTPUT = "session_output"
     COMMAND_CANCEL = "command_cancel"
     
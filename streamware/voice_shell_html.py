@@ -10,7 +10,7 @@ from pathlib import Path
 # WEB UI HTML
 # =============================================================================
 
-def get_voice_shell_html_from_template(ws_port: int, language: str = "en") -> str:
+def get_voice_shell_html_from_template(ws_port: int, language: str = "en", token: str = "") -> str:
     """Load HTML from template files (new modular approach)."""
     import socket
     hostname = socket.gethostname()
@@ -54,11 +54,13 @@ def get_voice_shell_html_from_template(ws_port: int, language: str = "en") -> st
         print(f"Warning: Could not load template: {e}")
     
     # Fallback to inline HTML
-    return get_voice_shell_html(ws_port)
+    return get_voice_shell_html(ws_port, token)
 
 
-def get_voice_shell_html(ws_port: int) -> str:
+def get_voice_shell_html(ws_port: int, token: str = "") -> str:
     """Generate the voice shell web UI HTML (inline fallback)."""
+    # Desktop launcher token, required by the WebSocket when set
+    ws_query = f"/?token={token}" if token else ""
     return f'''<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -358,7 +360,7 @@ def get_voice_shell_html(ws_port: int) -> str:
     </div>
     
     <script>
-        const WS_URL = "ws://" + window.location.hostname + ":{ws_port}";
+        const WS_URL = "ws://" + window.location.hostname + ":{ws_port}{ws_query}";
         let ws;
         let recognition;
         let synthesis = window.speechSynthesis;
@@ -502,6 +504,13 @@ def get_voice_shell_html(ws_port: int) -> str:
                     }}
                     break;
                     
+                case 'session_renamed':
+                    updateSessionsList(event.data.sessions);
+                    if (event.data.session.id === currentSessionId) {{
+                        document.getElementById('current-session-name').textContent = '(' + event.data.session.name + ')';
+                    }}
+                    break;
+                    
                 case 'session_switched':
                     currentSessionId = event.data.session.id;
                     document.getElementById('current-session-name').textContent = '(' + event.data.session.name + ')';
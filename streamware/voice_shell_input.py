@@ -469,6 +469,15 @@ class VoiceInputProcessorMixin:
                 ))
                 return
             
+            elif msg_type == "rename_session":
+                session = self.rename_session(content.get("id"), content.get("name"))
+                if session:
+                    await self.broadcast(Event(
+                        type=EventType.SESSION_RENAMED,
+                        data={"session": session.to_dict(), "sessions": self._get_sessions_list()}
+                    ))
+                return
+            
             elif msg_type == "get_sessions":
                 # Create first session if none exist
                 if not self.sessions:
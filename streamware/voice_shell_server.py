@@ -17,6 +17,7 @@ Then open http://localhost:8765 in browser
 """
 
 import asyncio
+import hmac
 import json
 import os
 import subprocess
@@ -24,6 +25,7 @@ import sys
 import time
 import threading
 import queue
+import signal
 from dataclasses import dataclass, field, asdict
 from datetime import datetime
 from enum import Enum
@@ -50,6 +52,49 @@ from .voice_shell_events import EventType, Event, EventStore, Session
 from .voice_shell_input import VoiceInputProcessorMixin
 from .voice_shell_html import get_voice_shell_html, get_voice_shell_html_from_template
 
+# Version of the command-line contract with the desktop launcher
+# (CONTRACT_VERSION in desktop/rust/voice-shell-app/src-tauri/src/launch.rs)
+LAUNCH_CONTRACT_VERSION = 1
+
+# Per-launch token set by the desktop launcher; when present, every HTTP
+# and WebSocket client must present it
+AUTH_TOKEN_ENV = "STREAMWARE_VOICE_SHELL_TOKEN"
+
+# Set to "stdin" by the desktop launcher, which holds the write end of our
+# stdin pipe open for as long as it lives
+WATCHDOG_ENV = "STREAMWARE_VOICE_SHELL_WATCHDOG"
+
+
+def _terminate_process_group():
+    """Take down this process and, if we lead one, our process group."""
+    if hasattr(os, "killpg") and os.getpgrp() == os.getpid():
+        os.killpg(os.getpgrp(), signal.SIGTERM)
+    os._exit(1)
+
+
+def start_parent_watchdog(stream=None, on_parent_exit=_terminate_process_group) -> threading.Thread:
+    """Call `on_parent_exit` once `stream` hits EOF, i.e. the launcher died."""
+    stream = stream or sys.stdin.buffer
+    
+    def watch():
+        while stream.read(1024):
+            pass
+        print("⚠️ Desktop launcher exited, shutting down", file=sys.stderr, flush=True)
+        on_parent_exit()
+    
+    thread = threading.Thread(target=watch, name="parent-watchdog", daemon=True)
+    thread.start()
+    return thread
+
+
+def strip_token(url: str) -> str:
+    """Drop the launcher token from `url`, keeping the path and other query parameters."""
+    from urllib.parse import urlparse, parse_qsl, urlencode
+    
+    parsed = urlparse(url)
+    query = [(key, value) for key, value in parse_qsl(parsed.query, keep_blank_values=True) if key != "token"]
+    return parsed._replace(query=urlencode(query)).geturl()
+
 
 class VoiceShellServer(VoiceInputProcessorMixin):
     """WebSocket server for voice-enabled shell interaction with multi-session support."""
@@ -61,9 +106,12 @@ class VoiceShellServer(VoiceInputProcessorMixin):
         model: str = "llama3.2",
         verbose: bool = False,
         default_language: str = "en",
+        http_port: Optional[int] = None,
     ):
         self.host = host
         self.port = port
+        self.http_port = http_port or port + 1
+        self.auth_token = os.environ.get(AUTH_TOKEN_ENV) or None
         self.model = model
         self.verbose = verbose
         
@@ -226,6 +274,15 @@ class VoiceShellServer(VoiceInputProcessorMixin):
             return session
         return None
     
+    def rename_session(self, session_id: str, name: str) -> Optional[Session]:
+        """Rename a session."""
+        session = self.sessions.get(session_id)
+        if session and name:
+            session.name = name
+            self.db.rename_session(session_id, name)
+            return session
+        return None
+    
     def close_session(self, session_id: str):
         """Close and cleanup a session."""
         print(f"🗑️ Closing session: {session_id}")
@@ -263,8 +320,25 @@ class VoiceShellServer(VoiceInputProcessorMixin):
                 return_exceptions=True
             )
     
+    def check_token(self, token: Optional[str]) -> bool:
+        """Check a client token against the launcher token (if any)."""
+        if not self.auth_token:
+            return True
+        # Compared as bytes: compare_digest rejects non-ASCII str
+        return bool(token) and hmac.compare_digest(token.encode(), self.auth_token.encode())
+    
     async def handle_client(self, websocket):
         """Handle a client connection."""
+        if self.auth_token:
+            from urllib.parse import urlparse, parse_qs
+            
+            # websockets < 14 exposes .path, newer versions .request.path
+            path = getattr(websocket, "path", None) or websocket.request.path
+            token = parse_qs(urlparse(path).query).get("token", [None])[0]
+            if not self.check_token(token):
+                await websocket.close(code=4401, reason="Unauthorized")
+                return
+        
         self.clients.add(websocket)
         client_id = str(uuid.uuid4())[:8]
         
@@ -636,7 +710,7 @@ class VoiceShellServer(VoiceInputProcessorMixin):
             print("❌ websockets package required. Install with: pip install websockets")
             return
         
-        http_port = self.port + 1
+        http_port = self.http_port
         
         print(f"🎤 Voice Shell Server starting...")
         print(f"   WebSocket: ws://localhost:{self.port}")
@@ -716,6 +790,17 @@ class VoiceShellServer(VoiceInputProcessorMixin):
                     pass
                 return None
             
+            def _request_token(self, query: dict):
+                """Get the launcher token from header, cookie or query."""
+                token = self.headers.get('X-Voice-Shell-Token')
+                if not token:
+                    cookies = http.cookies.SimpleCookie(self.headers.get('Cookie', ''))
+                    if 'vs_token' in cookies:
+                        token = cookies['vs_token'].value
+                if not token:
+                    token = query.get('token', [None])[0]
+                return token
+            
             def _send_json(self, data: dict, status: int = 200):
                 """Send JSON response."""
                 self.send_response(status)
@@ -728,12 +813,26 @@ class VoiceShellServer(VoiceInputProcessorMixin):
                 path = parsed.path
                 query = parse_qs(parsed.query)
                 
+                if not voice_shell_server.check_token(self._request_token(query)):
+                    self._send_json({"error": "Unauthorized"}, 401)
+                    return
+                
+                # Launcher token in the URL: keep it in a cookie and drop it from the address
+                if voice_shell_server.auth_token and 'token' in query:
+                    self.send_response(302)
+                    self.send_header("Set-Cookie", f"vs_token={voice_shell_server.auth_token}; Path=/; HttpOnly; SameSite=Strict")
+                    self.send_header("Location", strip_token(self.path))
+                    self.end_headers()
+                    return
+                
                 # Main page
                 if path == "/" or path == "/index.html":
                     self.send_response(200)
                     self.send_header("Content-type", "text/html")
                     self.end_headers()
-                    html = get_voice_shell_html_from_template(self.server.ws_port, self.server.language)
+                    html = get_voice_shell_html_from_template(
+                        self.server.ws_port, self.server.language, voice_shell_server.auth_token or ""
+                    )
                     self.wfile.write(html.encode())
                 
                 # Static files (CSS, JS)
@@ -787,6 +886,14 @@ class VoiceShellServer(VoiceInputProcessorMixin):
                     except Exception as e:
                         self._send_json({"error": str(e)}, 500)
                 
+                # Health check (used by the desktop launcher)
+                elif path == "/health":
+                    self._send_json({
+                        "status": "ok",
+                        "ws_port": self.server.ws_port,
+                        "language": self.server.language,
+                    })
+                
                 # Auth: Get current user
                 elif path == "/auth/me":
                     user = self._get_session_user()
@@ -839,6 +946,10 @@ class VoiceShellServer(VoiceInputProcessorMixin):
                 parsed = urlparse(self.path)
                 path = parsed.path
                 
+                if not voice_shell_server.check_token(self._request_token(parse_qs(parsed.query))):
+                    self._send_json({"error": "Unauthorized"}, 401)
+                    return
+                
                 # Auth: Request magic link
                 if path == "/auth/login":
                     try:
@@ -894,9 +1005,9 @@ class VoiceShellServer(VoiceInputProcessorMixin):
             def log_message(self, format, *args):
                 pass  # Suppress logging
         
-        # Run HTTP server on port+1
-        http_port = self.port + 1
-        server = HTTPServer(("0.0.0.0", http_port), Handler)
+        # Run HTTP server (port+1 unless configured)
+        http_port = self.http_port
+        server = HTTPServer((self.host, http_port), Handler)
         server.ws_port = self.port  # Store WS port for HTML to connect to
         server.language = self.language  # Use server's language
         self.http_server = server  # Store reference for cleanup
@@ -920,14 +1031,27 @@ def main():
     parser = argparse.ArgumentParser(description="Streamware Voice Shell Server")
     parser.add_argument("--host", default="0.0.0.0", help="Host to bind (default: 0.0.0.0)")
     parser.add_argument("--port", "-p", type=int, default=8765, help="WebSocket port (default: 8765)")
+    parser.add_argument("--http-port", type=int, default=None, help="HTTP UI port (default: port + 1)")
     parser.add_argument("--model", "-m", default="llama3.2", help="LLM model (default: llama3.2)")
+    parser.add_argument("--lang", "-l", default="en", help="Conversation language (default: en)")
+    parser.add_argument("--verbose", "-v", action="store_true", help="Verbose output")
+    parser.add_argument("--contract", type=int, default=LAUNCH_CONTRACT_VERSION, help=argparse.SUPPRESS)
     
     args = parser.parse_args()
     
+    if args.contract != LAUNCH_CONTRACT_VERSION:
+        parser.error(f"launch contract {args.contract} not supported (expected {LAUNCH_CONTRACT_VERSION})")
+    
+    if os.environ.get(WATCHDOG_ENV) == "stdin":
+        start_parent_watchdog()
+    
     server = VoiceShellServer(
         host=args.host,
         port=args.port,
         model=args.model,
+        verbose=args.verbose,
+        default_language=args.lang,
+        http_port=args.http_port,
     )
     
     try:
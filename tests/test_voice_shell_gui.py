@@ -3,6 +3,7 @@ Tests for Voice Shell GUI functionality.
 """
 import sys
 import os
+import threading
 sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
 
 
@@ -255,6 +256,64 @@ def test_server_language_init():
     assert server.language in ['en', 'pl', 'de']
 
 
+def test_server_http_port():
+    """Test that the HTTP port defaults to port + 1 and can be overridden."""
+    from streamware.voice_shell_server import VoiceShellServer
+    
+    assert VoiceShellServer(port=9993).http_port == 9994
+    assert VoiceShellServer(port=9993, http_port=9000).http_port == 9000
+
+
+def test_server_auth_token():
+    """Test that the launcher token is required only when configured."""
+    from streamware.voice_shell_server import VoiceShellServer, AUTH_TOKEN_ENV
+    
+    os.environ.pop(AUTH_TOKEN_ENV, None)
+    assert VoiceShellServer(port=9992).check_token(None)
+    
+    os.environ[AUTH_TOKEN_ENV] = "secret"
+    try:
+        server = VoiceShellServer(port=9992)
+        assert server.check_token("secret")
+        assert not server.check_token("wrong")
+        assert not server.check_token(None)
+        assert not server.check_token("é")
+    finally:
+        del os.environ[AUTH_TOKEN_ENV]
+
+
+def test_strip_token():
+    """Test that only the launcher token is dropped from a redirect target."""
+    from streamware.voice_shell_server import strip_token
+    
+    assert strip_token("/?token=secret") == "/"
+    assert strip_token("/?session=s2&token=secret&lang=pl") == "/?session=s2&lang=pl"
+    assert strip_token("/index.html?q=a+b&empty=&token=x") == "/index.html?q=a+b&empty="
+
+
+def test_page_carries_token():
+    """Test that the served page connects its WebSocket with the launcher token."""
+    from streamware.voice_shell_html import get_voice_shell_html_from_template
+    
+    assert ':8765/?token=secret"' in get_voice_shell_html_from_template(8765, "en", "secret")
+    assert "token=" not in get_voice_shell_html_from_template(8765, "en")
+
+
+def test_parent_watchdog():
+    """Test that the watchdog fires once the launcher's pipe closes."""
+    from streamware.voice_shell_server import start_parent_watchdog
+    
+    read_fd, write_fd = os.pipe()
+    fired = threading.Event()
+    with os.fdopen(read_fd, "rb") as stream:
+        thread = start_parent_watchdog(stream, on_parent_exit=fired.set)
+        assert not fired.wait(0.1)
+        
+        os.close(write_fd)
+        thread.join(timeout=2)
+        assert fired.is_set()
+
+
 def run_tests():
     """Run all tests and report results."""
     passed = 0
@@ -273,6 +332,11 @@ def run_tests():
         test_sessions_list,
         test_language_translations,
         test_server_language_init,
+        test_server_http_port,
+        test_server_auth_token,
+        test_strip_token,
+        test_page_carries_token,
+        test_parent_watchdog,
     ]
     
     print("🧪 Running Voice Shell GUI Tests\n")